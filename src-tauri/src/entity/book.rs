@@ -0,0 +1,148 @@
+use std::{collections::HashMap, path::Path};
+
+use regex::Regex;
+
+use crate::error::Result;
+
+use super::{
+    bookinfo::BookInfo,
+    bookmark::Position,
+    catalog::CatalogItem,
+    epub::Epub,
+    progress::ReadingProgress,
+    search::{find_matches, MAX_MATCHES},
+    txt::Txt,
+};
+
+/// 书本格式的通用接口
+///
+/// 阅读相关的 handler 只依赖该 trait, 新的格式实现该 trait 并在 open() 中注册后即可使用
+pub trait Book: Send {
+    /// 书本的公共信息
+    fn info(&self) -> &BookInfo;
+
+    /// 同步书本的公共信息
+    fn set_info(&mut self, info: BookInfo);
+
+    /// 获取目录树
+    fn get_catalog(&self) -> Vec<CatalogItem>;
+
+    /// 章节总数
+    fn page_count(&self) -> usize;
+
+    /// 当前章节的索引
+    fn current_page(&self) -> usize;
+
+    /// 跳转到指定章节, 章节不存在时返回 false
+    fn set_current_page(&mut self, num: usize) -> bool;
+
+    /// 获取当前章节的 HTML 内容
+    fn get_current_page(&mut self) -> String;
+
+    /// 获取指定章节的段落文本
+    fn get_page_text(&mut self, index: usize) -> Vec<String>;
+
+    /// 获取指定章节中锚点所在的段落索引
+    fn get_page_anchors(&mut self, _index: usize) -> HashMap<String, usize> {
+        HashMap::new()
+    }
+
+    /// 根据路径获取书本内的资源
+    fn get_resource(&mut self, path: &Path) -> Option<Vec<u8>>;
+
+    /// 获取书本的样式表
+    ///
+    /// 返回一个HashMap<String, String>
+    /// key为文件名，value为文件内容
+    fn get_css(&mut self) -> HashMap<String, String>;
+
+    fn go_next(&mut self) -> bool {
+        self.set_current_page(self.current_page() + 1)
+    }
+
+    fn go_prev(&mut self) -> bool {
+        // 直接减1容易发生下溢出, 需要判断当前是否为0
+        if self.current_page() == 0 {
+            return false;
+        }
+
+        self.set_current_page(self.current_page() - 1)
+    }
+
+    /// 根据当前章节中的段落定位所在的目录项, 返回目录项的序号
+    ///
+    /// 当前章节中锚点位于该段落之前的最后一个目录项即为所在的目录项,
+    /// 当前章节不在目录中时, 归属于之前最近的目录项
+    fn locate_catalog(&mut self, paragraph: usize) -> Option<usize> {
+        let current = self.current_page();
+        let anchors = self.get_page_anchors(current);
+        let catalog = self.get_catalog();
+
+        let mut result = None;
+        for item in CatalogItem::flatten(&catalog) {
+            if item.target > current {
+                continue;
+            }
+
+            let start = match &item.anchor {
+                Some(anchor) if item.target == current => {
+                    anchors.get(anchor).copied().unwrap_or(0)
+                }
+                _ => 0,
+            };
+            if item.target < current || start <= paragraph {
+                result = match result {
+                    Some((target, _)) if target > item.target => result,
+                    _ => Some((item.target, item.index)),
+                };
+            }
+        }
+
+        result.map(|(_, index)| index)
+    }
+
+    /// 在整本书中搜索, 返回匹配项的位置, 最多返回 MAX_MATCHES 个
+    fn search(&mut self, pattern: &Regex) -> Vec<Position> {
+        let mut list = Vec::new();
+
+        for index in 0..self.page_count() {
+            if list.len() >= MAX_MATCHES {
+                break;
+            }
+
+            let paragraphs = self.get_page_text(index);
+            list.extend(find_matches(
+                pattern,
+                index,
+                &paragraphs,
+                MAX_MATCHES - list.len(),
+            ));
+        }
+
+        list
+    }
+
+    /// 生成当前位置的阅读进度
+    ///
+    /// 参数 fraction 为当前章节内已阅读的比例, 取值范围为 0 ~ 1
+    fn get_progress(&self, paragraph: usize, offset: usize, fraction: f64) -> ReadingProgress {
+        let current = self.current_page();
+        let position = Position {
+            chapter: current,
+            paragraph,
+            offset,
+        };
+        let total = self.page_count().max(1) as f64;
+        let percentage = (current as f64 + fraction.clamp(0.0, 1.0)) / total * 100.0;
+
+        ReadingProgress::new(&self.info().id, position, percentage)
+    }
+}
+
+/// 根据书本文件的扩展名打开书本, 未知的扩展名按 Epub 处理
+pub fn open(info: &BookInfo) -> Result<Box<dyn Book>> {
+    match info.file_path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("txt") => Ok(Box::new(Txt::open(info.clone())?)),
+        _ => Ok(Box::new(Epub::open(info.clone())?)),
+    }
+}
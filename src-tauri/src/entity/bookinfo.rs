@@ -1,15 +1,33 @@
-use std::{collections::HashMap, fmt::Display, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
 use epub::doc::EpubDoc;
-use rusqlite::{params, Error};
+use log::warn;
+use rusqlite::{functions::FunctionFlags, params, params_from_iter, types::Value, Connection, Row};
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 use crate::{
-    utils::{common_utils::hash, config_utils::read_config},
+    entity::query::{BookPage, BookQuery},
+    error::{Error, Result},
+    utils::{
+        common_utils::hash,
+        config_utils::read_config,
+        cover_utils::{cover_extension, thumbnail_paths},
+    },
     CONN,
 };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 书本列表查询的列, 顺序与 book_info 表一致, 书本简介使用空字符串代替
+const LIST_COLUMNS: &str = "book_info.id, file_path, cover_path, title, creator, date,
+                            publisher, language, subject, '' AS description, last_open,
+                            source_path, missing, identifier, series, series_index, add_time";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct BookInfo {
     /// Epub的唯一标识符
     pub id: String,
@@ -20,6 +38,10 @@ pub struct BookInfo {
     /// 封面存储路径
     pub cover_path: PathBuf,
 
+    /// 封面缩略图的路径, 按宽度从小到大排列, 根据封面路径得出, 不保存在数据库中
+    #[serde(default)]
+    pub thumbnails: Vec<PathBuf>,
+
     /// 书本标题
     pub title: String,
 
@@ -41,29 +63,77 @@ pub struct BookInfo {
     /// 书本简介
     pub description: String,
 
+    /// 书本的标识符, 如 ISBN 或 UUID, txt 文件为空
+    pub identifier: String,
+
+    /// 书本所属的系列, 不属于任何系列时为空
+    pub series: String,
+
+    /// 书本在系列中的序号
+    pub series_index: f64,
+
     /// 本书上次打开的时间
+    #[ts(type = "number")]
     pub last_open: u64,
+
+    /// 本书导入书库的时间
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub add_time: u64,
+
+    /// 导入时的原始文件路径
+    pub source_path: PathBuf,
+
+    /// 原始文件是否已被移除, 仅用于监视文件夹中导入的书本
+    pub missing: bool,
+}
+
+/// 可编辑的书本元数据
+///
+/// 作者与分类有多个值时以逗号分隔, 与 BookInfo 中的格式一致
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct BookMetadata {
+    pub title: String,
+    pub creator: String,
+    pub date: String,
+    pub publisher: String,
+    pub language: String,
+    pub subject: String,
+    pub description: String,
+    pub series: String,
+    pub series_index: f64,
 }
 
 impl BookInfo {
-    pub fn new(path: PathBuf) -> Self {
-        let hash_code = hash(&path);
-        let mut book = EpubDoc::new(path).unwrap();
+    pub fn new(path: PathBuf) -> Result<Self> {
+        let hash_code = hash(&path)?;
+        let mut book = EpubDoc::new(&path).map_err(|err| Error::Epub(err.to_string()))?;
 
         let mut file_path = PathBuf::from(read_config().book.dir.clone());
         file_path.push(hash_code.clone() + ".epub");
 
-        let cover_data = book.get_cover().unwrap();
-        let mime: mime::Mime = cover_data.1.parse().unwrap();
-        let subtype = mime.subtype().as_str();
-        let mut cover_path = PathBuf::from(read_config().book.cover.clone());
-        cover_path.push(hash_code.clone() + "." + subtype);
+        // 没有封面时封面路径为空
+        let cover_path = match book.get_cover() {
+            Some((_, mime)) => {
+                let mut cover_path = PathBuf::from(read_config().book.cover.clone());
+                cover_path.push(hash_code.clone() + "." + cover_extension(&mime));
+                cover_path
+            }
+            None => {
+                warn!("书本没有封面: {:?}", path);
+                PathBuf::new()
+            }
+        };
 
         let metadata = book.metadata.clone();
+        let (series, series_index) = get_series(&metadata);
 
-        BookInfo {
+        Ok(BookInfo {
             id: hash_code,
             file_path,
+            thumbnails: thumbnail_paths(&cover_path),
             cover_path,
             title: get_value(&metadata, "title"),
             creator: get_value(&metadata, "creator"),
@@ -72,66 +142,190 @@ impl BookInfo {
             language: get_value(&metadata, "language"),
             subject: get_value(&metadata, "subject"),
             description: get_value(&metadata, "description"),
+            identifier: book.mdata("identifier").unwrap_or_default(),
+            series,
+            series_index,
             last_open: 0,
-        }
+            add_time: 0,
+            source_path: path,
+            missing: false,
+        })
     }
 
-    pub fn get_info_list() -> Result<Vec<BookInfo>, Error> {
+    /// 读取 epub 文件中的系列名称与序号
+    pub fn read_series(path: &Path) -> Result<(String, f64)> {
+        let book = EpubDoc::new(path).map_err(|err| Error::Epub(err.to_string()))?;
+
+        Ok(get_series(&book.metadata))
+    }
+
+    /// 根据 txt 文件构建书本信息, 标题为文件名, 其余元数据为空
+    pub fn new_txt(path: PathBuf) -> Result<Self> {
+        let hash_code = hash(&path)?;
+
+        let mut file_path = PathBuf::from(read_config().book.dir.clone());
+        file_path.push(hash_code.clone() + ".txt");
+
+        let title = match path.file_stem() {
+            Some(stem) => stem.to_string_lossy().to_string(),
+            None => String::new(),
+        };
+
+        Ok(BookInfo {
+            id: hash_code,
+            file_path,
+            cover_path: PathBuf::new(),
+            thumbnails: Vec::new(),
+            title,
+            creator: String::new(),
+            date: String::new(),
+            publisher: String::new(),
+            language: String::new(),
+            subject: String::new(),
+            description: String::new(),
+            identifier: String::new(),
+            series: String::new(),
+            series_index: 0.0,
+            last_open: 0,
+            add_time: 0,
+            source_path: path,
+            missing: false,
+        })
+    }
+
+    pub fn get_info_list() -> Result<Vec<BookInfo>> {
         let conn = CONN.lock().unwrap();
 
         let sql = "SELECT * FROM book_info;";
-        let mut stmt = conn.prepare(sql).unwrap();
-        let rows = stmt
-            .query_map([], |row| {
-                Ok(BookInfo {
-                    id: row.get(0).unwrap(),
-                    file_path: PathBuf::from(row.get::<usize, String>(1).unwrap()),
-                    cover_path: PathBuf::from(row.get::<usize, String>(2).unwrap()),
-                    title: row.get(3).unwrap(),
-                    creator: row.get(4).unwrap(),
-                    date: row.get(5).unwrap(),
-                    publisher: row.get(6).unwrap(),
-                    language: row.get(7).unwrap(),
-                    subject: row.get(8).unwrap(),
-                    description: row.get(9).unwrap(),
-                    last_open: row.get(10).unwrap(),
-                })
-            })
-            .unwrap();
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], BookInfo::from_row)?;
 
         let mut list = Vec::new();
         for row in rows {
-            match row {
-                Ok(info) => list.push(info),
-                Err(err) => return Err(err),
-            }
+            list.push(row?);
+        }
+
+        Ok(list)
+    }
+
+    /// 按查询条件获取一页书本, 同时返回满足条件的书本总数
+    ///
+    /// 列表中不包含书本简介, 需要时通过 get_specific_info 获取
+    pub fn query_info_list(query: &BookQuery) -> Result<BookPage> {
+        let conn = CONN.lock().unwrap();
+
+        let (condition, mut values) = query.where_clause();
+        let from = format!(
+            "FROM book_info LEFT JOIN reading_progress ON book_info.id = reading_progress.book_id
+                        WHERE {}",
+            condition
+        );
+
+        let sql = format!("SELECT count(*) {};", from);
+        let total = conn.query_row(&sql, params_from_iter(values.iter()), |row| row.get(0))?;
+
+        // LIMIT 为负数时不限制数量
+        let limit = query.limit.map_or(-1, |limit| limit as i64);
+        values.push(Value::Integer(limit));
+        values.push(Value::Integer(query.offset as i64));
+
+        let sql = format!(
+            "SELECT {} {} ORDER BY {} LIMIT ? OFFSET ?;",
+            LIST_COLUMNS,
+            from,
+            query.order_clause()
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_from_iter(values.iter()), BookInfo::from_row)?;
+
+        let mut books = Vec::new();
+        for row in rows {
+            books.push(row?);
+        }
+
+        Ok(BookPage { books, total })
+    }
+
+    /// 获取属于某个系列的全部书本, 按系列名称与序号排序, 不包含书本简介
+    pub fn get_series_books() -> Result<Vec<BookInfo>> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = format!(
+            "SELECT {} FROM book_info WHERE series != ''
+                        ORDER BY series COLLATE NOCASE, series_index, title COLLATE NOCASE;",
+            LIST_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], BookInfo::from_row)?;
+
+        let mut list = Vec::new();
+        for row in rows {
+            list.push(row?);
         }
 
         Ok(list)
     }
 
-    pub fn get_specific_info(id: &str) -> Result<BookInfo, Error> {
+    /// 同一系列中的下一本书, 即序号大于当前书本的第一本书
+    pub fn get_next_in_series(info: &BookInfo) -> Result<Option<BookInfo>> {
+        if info.series.is_empty() {
+            return Ok(None);
+        }
+
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT * FROM book_info WHERE series = ?1 COLLATE NOCASE AND series_index > ?2
+                        ORDER BY series_index LIMIT 1;";
+        match conn.query_row(
+            sql,
+            params![info.series, info.series_index],
+            BookInfo::from_row,
+        ) {
+            Ok(info) => Ok(Some(info)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn get_specific_info(id: &str) -> Result<BookInfo> {
         let conn = CONN.lock().unwrap();
 
         let sql = "SELECT * FROM book_info WHERE id = ?1;";
-        conn.query_row(sql, [id], |row| {
-            Ok(BookInfo {
-                id: row.get(0).unwrap(),
-                file_path: PathBuf::from(row.get::<usize, String>(1).unwrap()),
-                cover_path: PathBuf::from(row.get::<usize, String>(2).unwrap()),
-                title: row.get(3).unwrap(),
-                creator: row.get(4).unwrap(),
-                date: row.get(5).unwrap(),
-                publisher: row.get(6).unwrap(),
-                language: row.get(7).unwrap(),
-                subject: row.get(8).unwrap(),
-                description: row.get(9).unwrap(),
-                last_open: row.get(10).unwrap(),
-            })
+        match conn.query_row(sql, [id], BookInfo::from_row) {
+            Ok(info) => Ok(info),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Err(Error::NotFound("Book".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<BookInfo> {
+        let cover_path = PathBuf::from(row.get::<usize, String>(2)?);
+        // 缩略图在导入与迁移时生成, 不检查文件是否存在, 生成失败时由前端改用封面
+        let thumbnails = thumbnail_paths(&cover_path);
+
+        Ok(BookInfo {
+            id: row.get(0)?,
+            file_path: PathBuf::from(row.get::<usize, String>(1)?),
+            cover_path,
+            thumbnails,
+            title: row.get(3)?,
+            creator: row.get(4)?,
+            date: row.get(5)?,
+            publisher: row.get(6)?,
+            language: row.get(7)?,
+            subject: row.get(8)?,
+            description: row.get(9)?,
+            last_open: row.get(10)?,
+            source_path: PathBuf::from(row.get::<usize, String>(11)?),
+            missing: row.get(12)?,
+            identifier: row.get(13)?,
+            series: row.get(14)?,
+            series_index: row.get(15)?,
+            add_time: row.get(16)?,
         })
     }
 
-    pub fn insert_info(info: &Self) -> bool {
+    pub fn insert_info(info: &Self) -> Result<()> {
         let conn = CONN.lock().unwrap();
 
         let sql = "INSERT INTO book_info ( 
@@ -145,14 +339,21 @@ impl BookInfo {
                             language, 
                             subject, 
                             description, 
-                            last_open
+                            last_open,
+                            source_path,
+                            missing,
+                            identifier,
+                            series,
+                            series_index,
+                            add_time
                         ) VALUES (
-                            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11
+                            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                            ?17
                         );";
         let params = params![
             info.id,
-            info.file_path.to_str().unwrap(),
-            info.cover_path.to_str().unwrap(),
+            info.file_path.to_string_lossy(),
+            info.cover_path.to_string_lossy(),
             info.title,
             info.creator,
             info.date,
@@ -161,15 +362,20 @@ impl BookInfo {
             info.subject,
             info.description,
             info.last_open.to_string(),
+            info.source_path.to_string_lossy(),
+            info.missing,
+            info.identifier,
+            info.series,
+            info.series_index,
+            info.add_time,
         ];
 
-        match conn.execute(sql, params) {
-            Ok(_) => true,
-            Err(_) => false,
-        }
+        conn.execute(sql, params)?;
+
+        Ok(())
     }
 
-    pub fn update_info(info: &Self) -> bool {
+    pub fn update_info(info: &Self) -> Result<()> {
         let conn = CONN.lock().unwrap();
 
         let sql = "UPDATE book_info SET
@@ -182,11 +388,17 @@ impl BookInfo {
                             language = ?7, 
                             subject = ?8, 
                             description = ?9, 
-                            last_open = ?10
-                        WHERE id = ?11;";
+                            last_open = ?10,
+                            source_path = ?11,
+                            missing = ?12,
+                            identifier = ?13,
+                            series = ?14,
+                            series_index = ?15,
+                            add_time = ?16
+                        WHERE id = ?17;";
         let params = params![
-            info.file_path.to_str().unwrap(),
-            info.cover_path.to_str().unwrap(),
+            info.file_path.to_string_lossy(),
+            info.cover_path.to_string_lossy(),
             info.title,
             info.creator,
             info.date,
@@ -195,14 +407,132 @@ impl BookInfo {
             info.subject,
             info.description,
             info.last_open.to_string(),
+            info.source_path.to_string_lossy(),
+            info.missing,
+            info.identifier,
+            info.series,
+            info.series_index,
+            info.add_time,
             info.id,
         ];
 
-        match conn.execute(sql, params) {
-            Ok(1) => true,
-            Ok(_) | Err(_) => false,
+        match conn.execute(sql, params)? {
+            0 => Err(Error::NotFound("Book".to_string())),
+            _ => Ok(()),
         }
     }
+
+    /// 使用编辑后的元数据更新书本信息
+    pub fn apply_metadata(&mut self, metadata: &BookMetadata) {
+        self.title = metadata.title.trim().to_string();
+        self.creator = metadata.creator.trim().to_string();
+        self.date = metadata.date.trim().to_string();
+        self.publisher = metadata.publisher.trim().to_string();
+        self.language = metadata.language.trim().to_string();
+        self.subject = metadata.subject.trim().to_string();
+        self.description = metadata.description.trim().to_string();
+        self.series = metadata.series.trim().to_string();
+        self.series_index = metadata.series_index;
+    }
+
+    /// 删除书本信息, 同时删除书本的书签, 阅读进度, 全文索引与章节字数
+    ///
+    /// 阅读记录(reading_session)保留, 用于统计阅读总量与连续天数
+    ///
+    /// 书本不存在时返回 NotFound
+    pub fn remove_info(id: &str) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let count = tx.execute("DELETE FROM book_info WHERE id = ?1;", [id])?;
+        if count == 0 {
+            return Err(Error::NotFound("Book".to_string()));
+        }
+
+        for table in [
+            "book_mark",
+            "reading_progress",
+            "book_search",
+            "book_shelf",
+            "book_tag",
+            "chapter_words",
+        ] {
+            let sql = format!("DELETE FROM {} WHERE book_id = ?1;", table);
+            tx.execute(&sql, [id])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// 用于识别重复书本的标识, 由规范化后的标题, 作者与标识符组成
+    ///
+    /// 标题或作者为空时无法识别, 返回 None
+    pub fn dedup_key(&self) -> Option<String> {
+        let (title, creator) = (normalize(&self.title), normalize(&self.creator));
+        if title.is_empty() || creator.is_empty() {
+            return None;
+        }
+
+        // 去除 urn:isbn:, urn:uuid: 等前缀
+        let identifier = match self.identifier.rsplit_once(':') {
+            Some((_, value)) => value,
+            None => &self.identifier,
+        };
+
+        Some(format!("{}|{}|{}", title, creator, normalize(identifier)))
+    }
+
+    /// 查找书库中内容不同但标题, 作者与标识符相同的书本
+    ///
+    /// 先在数据库中按规范化后的标题与作者筛选, 再比较标识符
+    pub fn find_duplicate(info: &Self) -> Result<Option<BookInfo>> {
+        let key = match info.dedup_key() {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+
+        let conn = CONN.lock().unwrap();
+        register_normalize(&conn)?;
+
+        let sql = format!(
+            "SELECT {} FROM book_info
+                        WHERE id != ?1 AND normalize(title) = ?2 AND normalize(creator) = ?3;",
+            LIST_COLUMNS
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let params = params![info.id, normalize(&info.title), normalize(&info.creator)];
+        let rows = stmt.query_map(params, BookInfo::from_row)?;
+
+        for row in rows {
+            let book = row?;
+            if book.dedup_key().as_ref() == Some(&key) {
+                return Ok(Some(book));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+// 注册 SQL 函数 normalize, 规则与 normalize 相同
+fn register_normalize(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "normalize",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| Ok(normalize(&ctx.get::<String>(0)?)),
+    )
+}
+
+// 只保留字母与数字并转为小写, 忽略空白, 标点与大小写的差异
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
 }
 
 // 从metadata中获取值
@@ -213,6 +543,29 @@ fn get_value(data: &HashMap<String, Vec<String>>, key: &str) -> String {
     }
 }
 
+/// 系列名称与序号
+///
+/// 依次读取 calibre 的 `calibre:series` 与 EPUB3 的 `belongs-to-collection`,
+/// 序号分别对应 `calibre:series_index` 与 `group-position`, 无法解析时为 0
+fn get_series(data: &HashMap<String, Vec<String>>) -> (String, f64) {
+    let pairs = [
+        ("calibre:series", "calibre:series_index"),
+        ("belongs-to-collection", "group-position"),
+    ];
+
+    for (name, index) in pairs {
+        let series = get_value(data, name).trim().to_string();
+        if series.is_empty() {
+            continue;
+        }
+
+        let index = get_value(data, index).trim().parse().unwrap_or(0.0);
+        return (series, index);
+    }
+
+    (String::new(), 0.0)
+}
+
 impl Display for BookInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -230,3 +583,73 @@ impl PartialEq for BookInfo {
         self.id == other.id && self.title == other.title
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use rusqlite::Connection;
+
+    use super::{get_series, register_normalize, BookInfo};
+
+    fn info(title: &str, creator: &str, identifier: &str) -> BookInfo {
+        BookInfo {
+            title: title.to_string(),
+            creator: creator.to_string(),
+            identifier: identifier.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn dedup_test() {
+        let origin = info(
+            "The Rust Book",
+            "Steve Klabnik",
+            "urn:isbn:978-1-7185-0044-0",
+        );
+
+        assert_eq!(
+            origin.dedup_key(),
+            info("the rust  book", "Steve  Klabnik", "9781718500440").dedup_key()
+        );
+        assert_ne!(
+            origin.dedup_key(),
+            info("The Rust Book", "Steve Klabnik", "9781718503106").dedup_key()
+        );
+        assert_eq!(info("", "Ferris", "").dedup_key(), None);
+        // 作者为空的同名书本不视为重复
+        assert_eq!(info("The Rust Book", " ", "").dedup_key(), None);
+    }
+
+    #[test]
+    fn normalize_sql_test() {
+        let conn = Connection::open_in_memory().unwrap();
+        register_normalize(&conn).unwrap();
+
+        let value: String = conn
+            .query_row("SELECT normalize('The  Rust-Book 2');", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(value, "therustbook2");
+    }
+
+    #[test]
+    fn series_test() {
+        let mut data = HashMap::new();
+        assert_eq!(get_series(&data), (String::new(), 0.0));
+
+        data.insert(
+            "belongs-to-collection".to_string(),
+            vec!["Rust".to_string()],
+        );
+        data.insert("group-position".to_string(), vec!["2".to_string()]);
+        assert_eq!(get_series(&data), ("Rust".to_string(), 2.0));
+
+        // 优先使用 calibre 的系列信息
+        data.insert("calibre:series".to_string(), vec!["Ferris".to_string()]);
+        data.insert("calibre:series_index".to_string(), vec!["1.5".to_string()]);
+        assert_eq!(get_series(&data), ("Ferris".to_string(), 1.5));
+    }
+}
@@ -1,22 +1,79 @@
-use rusqlite::{params, Error as SqlError, Params};
+use rusqlite::{params, Params};
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
-use crate::{utils::common_utils::time_stamp, CONN};
+use crate::{
+    error::{Error, Result},
+    utils::common_utils::time_stamp,
+    CONN,
+};
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
 pub struct BookMark {
     pub book_id: String,
     mark_id: usize,
     start_position: Position,
     end_position: Position,
+
+    #[ts(type = "number")]
     pub create_time: u64,
+
+    /// 书签范围内的文本
+    pub text: String,
+
+    /// 高亮颜色, 使用 CSS 颜色值, 为空时使用默认颜色
+    pub color: String,
+
+    /// 书签样式
+    pub style: MarkStyle,
+
+    /// 笔记内容
+    pub note: String,
+
+    /// 标签
+    pub tags: Vec<String>,
+
+    /// 最后一次修改的时间
+    #[ts(type = "number")]
+    pub update_time: u64,
+}
+
+/// 书签样式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum MarkStyle {
+    #[default]
+    Highlight,
+    Underline,
+    Wavy,
+}
+
+impl MarkStyle {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MarkStyle::Highlight => "Highlight",
+            MarkStyle::Underline => "Underline",
+            MarkStyle::Wavy => "Wavy",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "Underline" => MarkStyle::Underline,
+            "Wavy" => MarkStyle::Wavy,
+            _ => MarkStyle::Highlight,
+        }
+    }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
-struct Position {
-    chapter: usize,
-    paragraph: usize,
-    offset: usize,
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Position {
+    pub chapter: usize,
+    pub paragraph: usize,
+    pub offset: usize,
 }
 
 impl BookMark {
@@ -47,25 +104,83 @@ impl BookMark {
         self
     }
 
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    pub fn color(mut self, color: &str) -> Self {
+        self.color = color.to_string();
+        self
+    }
+
+    pub fn style(mut self, style: MarkStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn note(mut self, note: &str) -> Self {
+        self.note = note.to_string();
+        self
+    }
+
+    pub fn tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        self
+    }
+
     pub fn update(mut self) -> Self {
         self.mark_id = BookMark::next_id();
         self.create_time = time_stamp();
+        self.update_time = self.create_time;
         self
     }
 
+    pub fn id(&self) -> usize {
+        self.mark_id
+    }
+
+    pub fn start(&self) -> &Position {
+        &self.start_position
+    }
+
+    pub fn end(&self) -> &Position {
+        &self.end_position
+    }
+
+    /// 结束位置不在起始位置之前, 按章节, 段落, 偏移量依次比较
     pub fn is_legle(&self) -> bool {
-        self.end_position.chapter >= self.start_position.chapter
-            && self.end_position.paragraph >= self.start_position.paragraph
-            && self.end_position.offset >= self.start_position.offset
+        let (start, end) = (&self.start_position, &self.end_position);
+
+        (end.chapter, end.paragraph, end.offset) >= (start.chapter, start.paragraph, start.offset)
     }
 
-    pub fn get_mark_list(id: &str) -> Result<Vec<BookMark>, SqlError> {
-        let sql = "SELECT * FROM book_mark WHERE book_id = ?1;";
+    // 书签范围不合法或超出书本的章节总数(page_count)时返回 InvalidArgument
+    fn check_range(&self, page_count: usize) -> Result<()> {
+        if !self.is_legle() {
+            return Err(Error::InvalidArgument(
+                "Bookmark end is before its start".to_string(),
+            ));
+        }
+
+        match self.end_position.chapter < page_count {
+            true => Ok(()),
+            false => Err(Error::InvalidArgument(format!(
+                "Bookmark chapter {} out of range",
+                self.end_position.chapter
+            ))),
+        }
+    }
+
+    /// 获取书本的全部书签, 按起始位置排序
+    pub fn get_mark_list(id: &str) -> Result<Vec<BookMark>> {
+        let sql = "SELECT * FROM book_mark WHERE book_id = ?1 
+                        ORDER BY start_chapter, start_paragraph, start_offset;";
 
         BookMark::query_mark_list(sql, [id])
     }
 
-    pub fn get_mark_list_by_chapter(id: &str, chapter: usize) -> Result<Vec<BookMark>, SqlError> {
+    pub fn get_mark_list_by_chapter(id: &str, chapter: usize) -> Result<Vec<BookMark>> {
         let sql = "SELECT * FROM book_mark WHERE book_id = ?1 AND start_chapter = ?2;";
 
         BookMark::query_mark_list(sql, params![id, chapter])
@@ -75,51 +190,59 @@ impl BookMark {
         id: &str,
         chapter: usize,
         paragraph: usize,
-    ) -> Result<Vec<BookMark>, SqlError> {
+    ) -> Result<Vec<BookMark>> {
         let sql = "SELECT * FROM book_mark WHERE book_id = ?1 AND start_chapter = ?2 AND start_paragraph = ?3;";
 
         BookMark::query_mark_list(sql, params![id, chapter, paragraph])
     }
 
-    fn query_mark_list<P>(sql: &str, params: P) -> Result<Vec<BookMark>, SqlError>
+    fn query_mark_list<P>(sql: &str, params: P) -> Result<Vec<BookMark>>
     where
         P: Params,
     {
         let conn = CONN.lock().unwrap();
 
-        let mut stmt = conn.prepare(sql).unwrap();
-        let rows = stmt
-            .query_map(params, |row| {
-                Ok(BookMark {
-                    book_id: row.get(0).unwrap(),
-                    mark_id: row.get(1).unwrap(),
-                    start_position: Position {
-                        chapter: row.get(2).unwrap(),
-                        paragraph: row.get(3).unwrap(),
-                        offset: row.get(4).unwrap(),
-                    },
-                    end_position: Position {
-                        chapter: row.get(5).unwrap(),
-                        paragraph: row.get(6).unwrap(),
-                        offset: row.get(7).unwrap(),
-                    },
-                    create_time: row.get(8).unwrap(),
-                })
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            Ok(BookMark {
+                book_id: row.get(0)?,
+                mark_id: row.get(1)?,
+                start_position: Position {
+                    chapter: row.get(2)?,
+                    paragraph: row.get(3)?,
+                    offset: row.get(4)?,
+                },
+                end_position: Position {
+                    chapter: row.get(5)?,
+                    paragraph: row.get(6)?,
+                    offset: row.get(7)?,
+                },
+                create_time: row.get(8)?,
+                text: row.get(9)?,
+                color: row.get(10)?,
+                style: MarkStyle::parse(&row.get::<usize, String>(11)?),
+                note: row.get(12)?,
+                tags: split_tags(&row.get::<usize, String>(13)?),
+                update_time: row.get(14)?,
             })
-            .unwrap(); // 只有当参数不匹配时才会返回 Err, 此处断言不会错误, 依赖于开发者的开发水平
+        })?;
 
         let mut list = Vec::new();
         for row in rows {
-            match row {
-                Ok(mark) => list.push(mark),
-                Err(err) => return Err(err),
-            }
+            list.push(row?);
         }
 
         Ok(list)
     }
 
-    pub fn insert_mark(mark: &Self) -> bool {
+    /// 添加书签, page_count 为书本的章节总数, 范围不合法时返回 InvalidArgument
+    pub fn insert_mark(mark: &Self, page_count: usize) -> Result<()> {
+        mark.check_range(page_count)?;
+
+        Self::save_mark(mark)
+    }
+
+    fn save_mark(mark: &Self) -> Result<()> {
         let conn = CONN.lock().unwrap();
 
         let sql = "INSERT INTO book_mark (
@@ -131,9 +254,15 @@ impl BookMark {
                             end_chapter,
                             end_paragraph,
                             end_offset,
-                            create_time
+                            create_time,
+                            text,
+                            color,
+                            style,
+                            note,
+                            tags,
+                            update_time
                         ) VALUES (
-                            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
+                            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15
                         );";
         let params = params![
             mark.book_id,
@@ -145,22 +274,83 @@ impl BookMark {
             mark.end_position.paragraph,
             mark.end_position.offset,
             mark.create_time,
+            mark.text,
+            mark.color,
+            mark.style.as_str(),
+            mark.note,
+            join_tags(&mark.tags),
+            mark.update_time,
+        ];
+
+        conn.execute(sql, params)?;
+
+        Ok(())
+    }
+
+    /// 更新书签的范围与批注内容, 书签不存在时返回 NotFound, 范围不合法时返回 InvalidArgument
+    pub fn update_mark(mark: &Self, page_count: usize) -> Result<()> {
+        mark.check_range(page_count)?;
+        let conn = CONN.lock().unwrap();
+
+        let sql = "UPDATE book_mark SET
+                            start_chapter = ?1,
+                            start_paragraph = ?2,
+                            start_offset = ?3,
+                            end_chapter = ?4,
+                            end_paragraph = ?5,
+                            end_offset = ?6,
+                            text = ?7,
+                            color = ?8,
+                            style = ?9,
+                            note = ?10,
+                            tags = ?11,
+                            update_time = ?12
+                        WHERE mark_id = ?13;";
+        let params = params![
+            mark.start_position.chapter,
+            mark.start_position.paragraph,
+            mark.start_position.offset,
+            mark.end_position.chapter,
+            mark.end_position.paragraph,
+            mark.end_position.offset,
+            mark.text,
+            mark.color,
+            mark.style.as_str(),
+            mark.note,
+            join_tags(&mark.tags),
+            mark.update_time,
+            mark.mark_id,
         ];
 
-        match conn.execute(sql, params) {
-            Ok(_) => true,
-            Err(_) => false,
+        match conn.execute(sql, params)? {
+            0 => Err(Error::NotFound("Bookmark".to_string())),
+            _ => Ok(()),
         }
     }
 
-    pub fn remove_mark(id: usize) -> bool {
+    pub fn remove_mark(id: usize) -> Result<()> {
         let conn = CONN.lock().unwrap();
 
         let sql = "DELETE FROM book_mark WHERE mark_id = ?1;";
-        match conn.execute(sql, [id]) {
-            Ok(_) => true,
-            Err(_) => false,
+        conn.execute(sql, [id])?;
+
+        Ok(())
+    }
+
+    /// 恢复已删除书本的书签, 书签 id 已被占用时使用新的 id, 书签范围保持原样
+    pub fn restore_mark(mut mark: Self) -> Result<()> {
+        let exist = {
+            let conn = CONN.lock().unwrap();
+
+            let sql = "SELECT count(*) FROM book_mark WHERE mark_id = ?1;";
+            conn.query_row(sql, [mark.mark_id], |row| row.get::<usize, usize>(0))?
+        };
+
+        if exist > 0 {
+            mark.mark_id = BookMark::next_id();
         }
+
+        BookMark::save_mark(&mark)
     }
 
     fn next_id() -> usize {
@@ -177,27 +367,53 @@ impl BookMark {
     }
 }
 
+// 标签以逗号分隔存储, 因此需要移除标签中的逗号
+fn join_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| tag.replace(',', " ").trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| tag.to_string())
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
-    use super::BookMark;
+    use super::{join_tags, split_tags, BookMark};
 
     #[test]
     fn db_test() {
         let mark1 = BookMark::new("id_1").start_pos(0, 0, 0).end_pos(1, 1, 1);
-        BookMark::insert_mark(&mark1);
+        let _ = BookMark::insert_mark(&mark1, 3);
 
         let mark2 = BookMark::new("id_1").start_pos(1, 0, 0).end_pos(2, 2, 2);
-        BookMark::insert_mark(&mark2);
+        let _ = BookMark::insert_mark(&mark2, 3);
 
         println!("{:?}\n", BookMark::get_mark_list("id_1"));
         println!("{:?}\n", BookMark::get_mark_list_by_chapter("id_1", 0));
         println!("{:?}\n", BookMark::get_mark_list_by_paragraph("id_1", 1, 0));
 
         for id in 0..BookMark::next_id() {
-            println!("{}", BookMark::remove_mark(id));
+            println!("{:?}", BookMark::remove_mark(id));
         }
     }
 
+    #[test]
+    fn tags_test() {
+        let mark = BookMark::default().tags(&["a", " b,c ", ""]);
+        let tags = join_tags(&mark.tags);
+
+        assert_eq!(tags, "a,b c");
+        assert_eq!(split_tags(&tags), vec!["a", "b c"]);
+        assert!(split_tags("").is_empty());
+    }
+
     #[test]
     fn legle_test() {
         let mark1 = BookMark::new("id_1").start_pos(0, 0, 0).end_pos(1, 1, 1);
@@ -205,5 +421,12 @@ mod test {
 
         let mark2 = BookMark::new("id_1").start_pos(1, 0, 0).end_pos(0, 1, 1);
         assert_eq!(mark2.is_legle(), false);
+
+        // 跨段落时结束偏移量可以小于起始偏移量
+        let mark3 = BookMark::new("id_1").start_pos(0, 1, 10).end_pos(0, 2, 3);
+        assert!(mark3.is_legle());
+        assert!(mark2.check_range(2).is_err());
+        assert!(mark1.check_range(2).is_ok());
+        assert!(mark1.check_range(1).is_err());
     }
 }
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::utils::config_utils::Setting;
+
+use super::word_count::WordCount;
+
+/// 目录项
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CatalogItem {
+    /// 目录名
+    pub label: String,
+
+    /// 目录层级, 顶层目录为 1
+    pub level: usize,
+
+    /// 目录项的序号, 按阅读顺序从 0 开始
+    pub index: usize,
+
+    /// 目录项指向的章节索引(spine 索引), 跳转章节时使用该值
+    pub target: usize,
+
+    /// 目录项指向的页面锚点, 对应链接中 # 后的部分
+    pub anchor: Option<String>,
+
+    /// 目录项的阅读顺序
+    pub play_order: usize,
+
+    /// 子目录
+    pub children: Vec<CatalogItem>,
+
+    /// 目录项包含的章节的单词数, 包含子目录的章节
+    pub words: usize,
+
+    /// 目录项包含的章节的中日文字符数
+    pub characters: usize,
+
+    /// 预计的阅读时间, 单位为分钟, 没有字数缓存时为 0
+    pub minutes: usize,
+}
+
+impl CatalogItem {
+    /// 按阅读顺序展开目录树
+    pub fn flatten(items: &[CatalogItem]) -> Vec<&CatalogItem> {
+        let mut list = Vec::new();
+
+        for item in items {
+            list.push(item);
+            list.extend(Self::flatten(&item.children));
+        }
+
+        list
+    }
+
+    /// 查找章节所属的目录项
+    ///
+    /// 返回阅读顺序中最后一个指向该章节或之前章节的目录项
+    pub fn find_by_chapter(items: &[CatalogItem], chapter: usize) -> Option<&CatalogItem> {
+        Self::flatten(items)
+            .into_iter()
+            .rev()
+            .find(|item| item.target <= chapter)
+    }
+
+    /// 根据各章节的字数填写目录项的字数与阅读时间
+    ///
+    /// 目录项包含从指向的章节开始, 到阅读顺序中下一个同级或上级目录项指向的章节之前的全部章节,
+    /// 指向同一章节的目录项均计入该章节的全部字数
+    pub fn fill_word_counts(items: &mut [CatalogItem], counts: &[WordCount], setting: &Setting) {
+        let flat = Self::flatten(items);
+        let ends = flat
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                flat[index + 1..]
+                    .iter()
+                    .find(|next| next.level <= item.level && next.target > item.target)
+                    .map_or(counts.len(), |next| next.target)
+            })
+            .collect::<Vec<_>>();
+
+        Self::fill(items, counts, setting, &ends, &mut 0);
+    }
+
+    fn fill(
+        items: &mut [CatalogItem],
+        counts: &[WordCount],
+        setting: &Setting,
+        ends: &[usize],
+        index: &mut usize,
+    ) {
+        for item in items {
+            let end = ends[*index];
+            *index += 1;
+
+            let mut count = WordCount::default();
+            for chapter in counts.get(item.target..end).unwrap_or_default() {
+                count += *chapter;
+            }
+
+            item.words = count.words;
+            item.characters = count.characters;
+            item.minutes = count.minutes(setting);
+            Self::fill(&mut item.children, counts, setting, ends, index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{entity::word_count::WordCount, utils::config_utils::Setting};
+
+    use super::CatalogItem;
+
+    fn item(level: usize, target: usize, children: Vec<CatalogItem>) -> CatalogItem {
+        CatalogItem {
+            level,
+            target,
+            children,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fill_test() {
+        let mut catalog = vec![
+            item(
+                1,
+                0,
+                vec![item(2, 1, vec![]), item(2, 1, vec![]), item(2, 2, vec![])],
+            ),
+            item(1, 3, vec![]),
+        ];
+        let counts = [
+            WordCount {
+                words: 100,
+                characters: 0,
+            },
+            WordCount {
+                words: 0,
+                characters: 800,
+            },
+            WordCount {
+                words: 250,
+                characters: 0,
+            },
+            WordCount {
+                words: 1,
+                characters: 1,
+            },
+        ];
+        CatalogItem::fill_word_counts(&mut catalog, &counts, &Setting::default());
+
+        assert_eq!(catalog[0].words, 350);
+        assert_eq!(catalog[0].characters, 800);
+        assert_eq!(catalog[0].minutes, 4);
+        // 指向同一章节的目录项都计入该章节
+        assert_eq!(catalog[0].children[0].characters, 800);
+        assert_eq!(catalog[0].children[1].characters, 800);
+        assert_eq!(catalog[0].children[2].words, 250);
+        assert_eq!(catalog[0].children[2].minutes, 1);
+        assert_eq!(catalog[1].minutes, 1);
+    }
+}
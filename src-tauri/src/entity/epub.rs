@@ -2,13 +2,19 @@ use std::{
     collections::{BTreeMap, HashMap},
     fs::File,
     io::BufReader,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
-use epub::doc::EpubDoc;
+use epub::doc::{EpubDoc, NavPoint};
+use log::warn;
 use scraper::{Html, Selector};
 
-use super::bookinfo::BookInfo;
+use crate::{
+    error::{Error, Result},
+    utils::content_utils::{anchors, paragraphs},
+};
+
+use super::{book::Book, bookinfo::BookInfo, catalog::CatalogItem};
 
 #[derive(Debug)]
 pub struct Epub {
@@ -19,100 +25,142 @@ pub struct Epub {
     /// 原始数据
     data: EpubDoc<BufReader<File>>,
 
-    /// 目录信息
-    catalog: BTreeMap<usize, (PathBuf, String)>,
+    /// 目录信息, 仅用于展示章节名与跳转
+    catalog: Vec<CatalogItem>,
 
-    /// 目录到资源的映射
-    resources_mapping: BTreeMap<usize, usize>,
-
-    /// 当前页面
+    /// 当前页面, 即 spine 中的索引
     current_page: usize,
 }
 
 impl Epub {
-    pub fn new(path: &PathBuf) -> Self {
-        let data = EpubDoc::new(path).unwrap();
-        let catalog = Self::init_catalog(&data);
-        let mapping = Self::init_resources_mapping(&data, &catalog);
+    /// 打开书库中保存的 epub 文件
+    pub fn open(info: BookInfo) -> Result<Self> {
+        let data = EpubDoc::new(&info.file_path).map_err(|err| Error::Epub(err.to_string()))?;
+        let spine = Self::init_spine(&data);
+
+        let mut index = 0;
+        let catalog = Self::init_catalog(&data.toc, &spine, 1, &mut index);
 
-        Epub {
-            info: BookInfo::new(path.clone()),
+        Ok(Epub {
+            info,
             data,
             catalog,
-            resources_mapping: mapping,
             current_page: 0,
-        }
+        })
     }
 
-    /// 初始化目录
-    ///
-    /// 返回一个BTreeMap<usize, (PathBuf, String)>
-    /// key: 章节索引;
-    /// value: (PathBuf: 章节文件路径; String: 章节名)
-    fn init_catalog(data: &EpubDoc<BufReader<File>>) -> BTreeMap<usize, (PathBuf, String)> {
-        let toc = &data.toc;
-        let mut key: usize = 0;
-        let mut catalog = BTreeMap::new();
-
-        for item in toc {
-            catalog.insert(key, (item.content.clone(), item.label.clone()));
-            key += 1;
-        }
+    /// 早期版本以目录项的序号作为章节索引, 返回每个序号对应的 spine 索引
+    pub fn legacy_chapters(path: &Path) -> Result<Vec<usize>> {
+        let data = EpubDoc::new(path).map_err(|err| Error::Epub(err.to_string()))?;
+        let spine = Self::init_spine(&data);
 
-        catalog
+        Ok(Self::legacy_targets(&data.toc, &spine))
     }
 
-    fn init_resources_mapping(
-        data: &EpubDoc<BufReader<File>>,
-        catalog: &BTreeMap<usize, (PathBuf, String)>,
-    ) -> BTreeMap<usize, usize> {
-        let mut index: usize = 0;
+    // 早期版本只为顶层目录项编号, 子目录项不占用序号.
+    // 指向的资源不存在时早期版本无法打开该书, 之后的序号也无从对应, 因此在此截断
+    fn legacy_targets(points: &[NavPoint], spine: &BTreeMap<PathBuf, usize>) -> Vec<usize> {
+        points
+            .iter()
+            .map_while(|point| spine.get(&split_fragment(&point.content).0).copied())
+            .collect()
+    }
+
+    /// 初始化资源路径到 spine 索引的映射
+    fn init_spine(data: &EpubDoc<BufReader<File>>) -> BTreeMap<PathBuf, usize> {
         let mut spine_to_resource = BTreeMap::new();
 
-        for current in &data.spine {
-            let (path, _) = &data.resources.get(current).unwrap();
-            spine_to_resource.insert(path.clone(), index);
-            index += 1;
+        for (index, current) in data.spine.iter().enumerate() {
+            if let Some((path, _)) = data.resources.get(current) {
+                spine_to_resource.insert(path.clone(), index);
+            }
         }
 
-        catalog
-            .iter()
-            .map(|(index, (path, _))| {
-                let target = spine_to_resource.get(path).unwrap();
-
-                (index.clone(), target.clone())
-            })
-            .collect::<BTreeMap<usize, usize>>()
+        spine_to_resource
     }
 
-    pub fn get_catalog(&self) -> Vec<String> {
+    /// 初始化目录
+    ///
+    /// 按阅读顺序为每个目录项分配序号(index),
+    /// 指向的资源不在 spine 中的目录项会被忽略
+    fn init_catalog(
+        points: &[NavPoint],
+        spine: &BTreeMap<PathBuf, usize>,
+        level: usize,
+        index: &mut usize,
+    ) -> Vec<CatalogItem> {
         let mut catalog = Vec::new();
-        for (_, (_, label)) in &self.catalog {
-            catalog.push(label.clone());
+
+        for point in points {
+            let (path, anchor) = split_fragment(&point.content);
+            let target = match spine.get(&path) {
+                Some(target) => *target,
+                None => {
+                    warn!("目录项 {} 指向的资源不存在: {:?}", point.label, path);
+                    continue;
+                }
+            };
+
+            let current = *index;
+            *index += 1;
+
+            catalog.push(CatalogItem {
+                label: point.label.clone(),
+                level,
+                index: current,
+                target,
+                anchor,
+                play_order: point.play_order,
+                children: Self::init_catalog(&point.children, spine, level + 1, index),
+                ..Default::default()
+            });
         }
 
         catalog
     }
+}
 
-    pub fn go_next(&mut self) -> bool {
-        self.set_current_page(self.current_page + 1)
-    }
+/// 拆分目录链接中的路径与锚点
+fn split_fragment(content: &Path) -> (PathBuf, Option<String>) {
+    let content = content.to_string_lossy();
 
-    pub fn go_prev(&mut self) -> bool {
-        // 直接减1容易发生下溢出, 需要判断当前是否为0
-        if self.current_page == 0 {
-            return false;
+    match content.split_once('#') {
+        Some((path, anchor)) if !anchor.is_empty() => {
+            (PathBuf::from(path), Some(anchor.to_string()))
         }
+        Some((path, _)) => (PathBuf::from(path), None),
+        None => (PathBuf::from(content.as_ref()), None),
+    }
+}
+
+impl Book for Epub {
+    fn info(&self) -> &BookInfo {
+        &self.info
+    }
+
+    fn set_info(&mut self, info: BookInfo) {
+        self.info = info;
+    }
+
+    fn get_catalog(&self) -> Vec<CatalogItem> {
+        self.catalog.clone()
+    }
 
-        self.set_current_page(self.current_page - 1)
+    /// 章节总数即 spine 的长度, 不在目录中的页面同样可以阅读
+    fn page_count(&self) -> usize {
+        self.data.spine.len()
     }
 
-    pub fn set_current_page(&mut self, num: usize) -> bool {
+    fn current_page(&self) -> usize {
+        self.current_page
+    }
+
+    fn set_current_page(&mut self, num: usize) -> bool {
         // self.data.get_num_pages()获取到的是resources的数量,
         // 而该数量与真实的章节数量是不同的, 无法使用该方法获取章节总数
         // if num <= self.data.get_num_pages()
 
-        if num < self.catalog.len() {
+        if num < self.data.spine.len() {
             self.current_page = num;
 
             return true;
@@ -121,42 +169,114 @@ impl Epub {
         false
     }
 
-    pub fn get_current_page(&mut self) -> String {
+    fn get_current_page(&mut self) -> String {
         // get_current_with_epub_uris()函数获取的页面中,
         // 资源使用的EpubURI, 而get_resource_by_path()函数获取的页面中,
         // 资源使用的是相对路径, 所以需要使用get_current_with_epub_uris()函数获取页面.
-        // 则需要将我们维护的catalog与EpubDoc中的资源进行匹配, 才能获取到正确的页面.
-        self.data.set_current_page(
-            self.resources_mapping
-                .get(&self.current_page)
-                .unwrap()
-                .clone(),
-        );
-        let page = self.data.get_current_with_epub_uris().unwrap();
-
-        let page = String::from_utf8(page).unwrap();
+        // current_page 即为 spine 中的索引, 可以直接同步到EpubDoc中.
+        // 页面无法读取时返回空内容, 不影响其他章节的阅读
+        self.data.set_current_page(self.current_page);
+        let page = match self.data.get_current_with_epub_uris() {
+            Ok(page) => String::from_utf8_lossy(&page).to_string(),
+            Err(err) => {
+                warn!("读取页面 {} 失败: {}", self.current_page, err);
+                return String::new();
+            }
+        };
 
         let document = Html::parse_document(&page);
         let body_selector = Selector::parse("body").unwrap();
-        let body = document.select(&body_selector).next().unwrap();
-        let content = body.inner_html();
-        content
+        match document.select(&body_selector).next() {
+            Some(body) => body.inner_html(),
+            None => String::new(),
+        }
+    }
+
+    /// 获取指定章节的段落文本
+    fn get_page_text(&mut self, index: usize) -> Vec<String> {
+        let id = match self.data.spine.get(index) {
+            Some(id) => id.clone(),
+            None => return Vec::new(),
+        };
+
+        match self.data.get_resource_str(&id) {
+            Some((page, _)) => paragraphs(&page),
+            None => Vec::new(),
+        }
+    }
+
+    fn get_page_anchors(&mut self, index: usize) -> HashMap<String, usize> {
+        let id = match self.data.spine.get(index) {
+            Some(id) => id.clone(),
+            None => return HashMap::new(),
+        };
+
+        match self.data.get_resource_str(&id) {
+            Some((page, _)) => anchors(&page),
+            None => HashMap::new(),
+        }
+    }
+
+    fn get_resource(&mut self, path: &Path) -> Option<Vec<u8>> {
+        self.data.get_resource_by_path(path)
     }
 
     /// 获取Epub的css
     ///
     /// 返回一个HashMap<String, Vec<u8>>
     /// key为文件名，value为文件内容
-    pub fn get_css(&mut self) -> HashMap<String, String> {
+    fn get_css(&mut self) -> HashMap<String, String> {
         let mut css_list = HashMap::new();
 
         for (name, (path, mime)) in self.data.resources.clone() {
             if mime == "text/css" {
-                let css = String::from_utf8(self.data.get_resource_by_path(path).unwrap()).unwrap();
-                css_list.insert(name + ".css", css);
+                if let Some(css) = self.data.get_resource_by_path(path) {
+                    css_list.insert(name + ".css", String::from_utf8_lossy(&css).to_string());
+                }
             }
         }
 
         css_list
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(label: &str, content: &str, children: Vec<NavPoint>) -> NavPoint {
+        NavPoint {
+            label: label.to_string(),
+            content: PathBuf::from(content),
+            children,
+            play_order: 0,
+        }
+    }
+
+    #[test]
+    fn legacy_targets_test() {
+        let spine = ["cover.xhtml", "ch1.xhtml", "ch1-1.xhtml", "ch2.xhtml"]
+            .iter()
+            .enumerate()
+            .map(|(index, path)| (PathBuf::from(path), index))
+            .collect::<BTreeMap<_, _>>();
+
+        let toc = vec![
+            point("封面", "cover.xhtml", Vec::new()),
+            point(
+                "第一章",
+                "ch1.xhtml",
+                vec![point("第一节", "ch1-1.xhtml#sec", Vec::new())],
+            ),
+            point("第二章", "ch2.xhtml#top", Vec::new()),
+        ];
+        assert_eq!(Epub::legacy_targets(&toc, &spine), vec![0, 1, 3]);
+
+        let toc = vec![
+            point("第一章", "ch1.xhtml", Vec::new()),
+            point("缺失", "missing.xhtml", Vec::new()),
+            point("第二章", "ch2.xhtml", Vec::new()),
+        ];
+        assert_eq!(Epub::legacy_targets(&toc, &spine), vec![1]);
+    }
+}
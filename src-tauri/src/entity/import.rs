@@ -0,0 +1,63 @@
+use serde::Serialize;
+use ts_rs::TS;
+
+use super::notification::Notification;
+
+/// 单个文件的导入阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[ts(export)]
+pub enum ImportStage {
+    /// 解析书本信息
+    Parsing,
+
+    /// 保存书本文件与封面
+    Saving,
+
+    /// 建立全文索引
+    Indexing,
+
+    /// 导入结束, 无论成功与否
+    Finished,
+}
+
+impl ImportStage {
+    /// 该阶段开始时, 单个文件已完成的比例
+    pub fn weight(&self) -> f64 {
+        match self {
+            ImportStage::Parsing => 0.0,
+            ImportStage::Saving => 0.2,
+            ImportStage::Indexing => 0.6,
+            ImportStage::Finished => 1.0,
+        }
+    }
+}
+
+/// 导入进度, 通过 `import://progress` 事件发送给前端
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ImportProgress {
+    /// 正在导入的文件名
+    pub file: String,
+
+    pub stage: ImportStage,
+
+    /// 正在导入的文件序号, 从 0 开始
+    pub index: usize,
+
+    /// 本次导入的文件总数
+    pub total: usize,
+
+    /// 整体进度, 范围为 0 - 100
+    pub percent: f64,
+}
+
+/// 导入结束时的汇总, 通过 `import://done` 事件发送给前端
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ImportSummary {
+    /// 每个已处理文件的导入结果
+    pub results: Vec<Notification>,
+
+    /// 导入是否被取消, 取消前已导入的书本会保留
+    pub cancelled: bool,
+}
@@ -1,4 +1,19 @@
+pub mod book;
 pub mod bookinfo;
 pub mod bookmark;
+pub mod catalog;
 pub mod epub;
+pub mod import;
 pub mod notification;
+pub mod progress;
+pub mod query;
+pub mod response;
+pub mod search;
+pub mod series;
+pub mod session;
+pub mod shelf;
+pub mod stats;
+pub mod tag;
+pub mod trash;
+pub mod txt;
+pub mod word_count;
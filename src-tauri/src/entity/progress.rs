@@ -0,0 +1,82 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{error::Result, utils::common_utils::time_stamp, CONN};
+
+use super::bookmark::Position;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ReadingProgress {
+    /// 书本的唯一标识符
+    pub book_id: String,
+
+    /// 当前阅读的位置
+    pub position: Position,
+
+    /// 阅读进度百分比, 取值范围为 0 ~ 100
+    pub percentage: f64,
+
+    /// 进度更新时间
+    #[ts(type = "number")]
+    pub update_time: u64,
+}
+
+impl ReadingProgress {
+    pub fn new(book_id: &str, position: Position, percentage: f64) -> Self {
+        ReadingProgress {
+            book_id: book_id.to_string(),
+            position,
+            percentage: percentage.clamp(0.0, 100.0),
+            update_time: time_stamp(),
+        }
+    }
+
+    pub fn get_progress(id: &str) -> Result<ReadingProgress> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT * FROM reading_progress WHERE book_id = ?1;";
+        conn.query_row(sql, [id], |row| {
+            Ok(ReadingProgress {
+                book_id: row.get(0)?,
+                position: Position {
+                    chapter: row.get(1)?,
+                    paragraph: row.get(2)?,
+                    offset: row.get(3)?,
+                },
+                percentage: row.get(4)?,
+                update_time: row.get(5)?,
+            })
+        })
+        .map_err(|err| err.into())
+    }
+
+    /// 保存阅读进度, 每本书只保留最新的一条记录
+    pub fn save_progress(progress: &Self) -> Result<()> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = "INSERT OR REPLACE INTO reading_progress (
+                            book_id,
+                            chapter,
+                            paragraph,
+                            offset,
+                            percentage,
+                            update_time
+                        ) VALUES (
+                            ?1, ?2, ?3, ?4, ?5, ?6
+                        );";
+        let params = params![
+            progress.book_id,
+            progress.position.chapter,
+            progress.position.paragraph,
+            progress.position.offset,
+            progress.percentage,
+            progress.update_time,
+        ];
+
+        conn.execute(sql, params)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::bookinfo::BookInfo;
+
+/// 阅读进度达到该值时视为已读完, 最后一页的进度不一定为 100
+const FINISHED_PERCENTAGE: f64 = 99.0;
+
+/// 书本列表的排序方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum SortKey {
+    Title,
+    Creator,
+    #[default]
+    LastOpen,
+    AddTime,
+    Progress,
+}
+
+/// 阅读状态, 根据阅读进度判断
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum ReadStatus {
+    Unread,
+    Reading,
+    Finished,
+}
+
+/// 书本列表的查询条件, 所有条件均为可选, 同时提供时返回同时满足的书本
+#[derive(Debug, Default, Clone, Deserialize, TS)]
+#[serde(default)]
+#[ts(export)]
+pub struct BookQuery {
+    pub sort: SortKey,
+
+    /// 是否倒序排列
+    pub descending: bool,
+
+    /// 书架的 id
+    #[ts(optional)]
+    pub shelf: Option<usize>,
+
+    #[ts(optional)]
+    pub tag: Option<String>,
+
+    #[ts(optional)]
+    pub language: Option<String>,
+
+    /// 作者, 返回作者中包含该值的书本
+    #[ts(optional)]
+    pub creator: Option<String>,
+
+    /// 分类, 返回分类中包含该值的书本
+    #[ts(optional)]
+    pub subject: Option<String>,
+
+    #[ts(optional)]
+    pub status: Option<ReadStatus>,
+
+    /// 关键字, 在标题, 作者, 出版社, 分类与系列中查找
+    #[ts(optional)]
+    pub keyword: Option<String>,
+
+    /// 每页的数量, 为空时返回全部书本
+    #[ts(optional)]
+    pub limit: Option<usize>,
+
+    pub offset: usize,
+}
+
+/// 书本列表的一页
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct BookPage {
+    /// 当前页的书本, 不包含书本简介
+    pub books: Vec<BookInfo>,
+
+    /// 满足查询条件的书本总数
+    pub total: usize,
+}
+
+impl BookQuery {
+    /// 生成筛选条件与对应的参数, 没有条件时返回 "1"
+    ///
+    /// 条件中的阅读进度来自 reading_progress 表, 需要与 book_info 连接查询
+    pub fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(shelf) = self.shelf {
+            conditions
+                .push("id IN (SELECT book_id FROM book_shelf WHERE shelf_id = ?)".to_string());
+            values.push(Value::Integer(shelf as i64));
+        }
+        if let Some(tag) = non_empty(&self.tag) {
+            conditions.push("id IN (SELECT book_id FROM book_tag WHERE tag = ?)".to_string());
+            values.push(Value::Text(tag.to_string()));
+        }
+        if let Some(language) = non_empty(&self.language) {
+            conditions.push("language = ? COLLATE NOCASE".to_string());
+            values.push(Value::Text(language.to_string()));
+        }
+        if let Some(creator) = non_empty(&self.creator) {
+            conditions.push("creator LIKE ? ESCAPE '\\'".to_string());
+            values.push(like_pattern(creator));
+        }
+        if let Some(subject) = non_empty(&self.subject) {
+            conditions.push("subject LIKE ? ESCAPE '\\'".to_string());
+            values.push(like_pattern(subject));
+        }
+        if let Some(status) = self.status {
+            let percentage = "coalesce(percentage, 0)";
+            conditions.push(match status {
+                ReadStatus::Unread => format!("{} = 0", percentage),
+                ReadStatus::Reading => {
+                    format!("{0} > 0 AND {0} < {1}", percentage, FINISHED_PERCENTAGE)
+                }
+                ReadStatus::Finished => format!("{} >= {}", percentage, FINISHED_PERCENTAGE),
+            });
+        }
+        if let Some(keyword) = non_empty(&self.keyword) {
+            let columns = ["title", "creator", "publisher", "subject", "series"];
+            let condition = columns
+                .iter()
+                .map(|column| format!("{} LIKE ? ESCAPE '\\'", column))
+                .collect::<Vec<String>>()
+                .join(" OR ");
+
+            conditions.push(format!("({})", condition));
+            values.extend(columns.iter().map(|_| like_pattern(keyword)));
+        }
+
+        match conditions.is_empty() {
+            true => ("1".to_string(), values),
+            false => (conditions.join(" AND "), values),
+        }
+    }
+
+    /// 生成排序条件, 排序的值相同时按导入顺序排列
+    pub fn order_clause(&self) -> String {
+        let column = match self.sort {
+            SortKey::Title => "title COLLATE NOCASE",
+            SortKey::Creator => "creator COLLATE NOCASE",
+            SortKey::LastOpen => "last_open",
+            SortKey::AddTime => "add_time",
+            SortKey::Progress => "coalesce(percentage, 0)",
+        };
+        let order = match self.descending {
+            true => "DESC",
+            false => "ASC",
+        };
+
+        format!("{0} {1}, book_info.rowid {1}", column, order)
+    }
+}
+
+// 去除两端空白, 值为空时视为没有该条件
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+// 转义 LIKE 中的通配符, 匹配包含该值的内容
+fn like_pattern(value: &str) -> Value {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    Value::Text(format!("%{}%", escaped))
+}
+
+#[cfg(test)]
+mod test {
+    use rusqlite::types::Value;
+
+    use super::{BookQuery, ReadStatus, SortKey};
+
+    #[test]
+    fn clause_test() {
+        let (condition, values) = BookQuery::default().where_clause();
+        assert_eq!(condition, "1");
+        assert!(values.is_empty());
+
+        let query = BookQuery {
+            sort: SortKey::Title,
+            descending: true,
+            creator: Some(" 50%_off ".to_string()),
+            tag: Some("  ".to_string()),
+            status: Some(ReadStatus::Reading),
+            keyword: Some("rust".to_string()),
+            ..Default::default()
+        };
+        let (condition, values) = query.where_clause();
+
+        // 空白的标签不作为条件
+        assert!(!condition.contains("book_tag"));
+        assert!(condition.contains("coalesce(percentage, 0) > 0"));
+        assert_eq!(values[0], Value::Text("%50\\%\\_off%".to_string()));
+        assert_eq!(values.len(), 6);
+        assert_eq!(
+            query.order_clause(),
+            "title COLLATE NOCASE DESC, book_info.rowid DESC"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use log::warn;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::error::Result;
+
+use super::{bookmark::Position, notification::Notification};
+
+/// 所有 command 统一的返回值
+///
+/// 成功时 data 为返回的数据, 失败时 notification 为需要展示给用户的提示信息
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct Response<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub notification: Option<Notification>,
+}
+
+impl<T> Response<T> {
+    pub fn ok(data: T) -> Self {
+        Response {
+            success: true,
+            data: Some(data),
+            notification: None,
+        }
+    }
+}
+
+impl<T> From<Result<T>> for Response<T> {
+    fn from(result: Result<T>) -> Self {
+        match result {
+            Ok(data) => Response::ok(data),
+            Err(err) => {
+                warn!("处理请求时发生错误: {}", err);
+
+                Response {
+                    success: false,
+                    data: None,
+                    notification: Some(Notification::from(&err)),
+                }
+            }
+        }
+    }
+}
+
+/// 打开书本时返回的内容
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct OpenedBook {
+    /// 当前章节的 HTML 内容
+    pub content: String,
+
+    /// 上一次的阅读位置
+    pub progress: Position,
+}
+
+/// 跳转章节时返回的内容
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ChapterContent {
+    /// 章节的 HTML 内容
+    pub content: String,
+
+    /// 跳转的锚点
+    pub anchor: Option<String>,
+
+    /// 锚点所在的段落索引, 没有锚点或锚点不存在时为 0
+    pub paragraph: usize,
+}
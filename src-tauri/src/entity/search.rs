@@ -0,0 +1,310 @@
+use regex::{Regex, RegexBuilder};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    error::{Error, Result},
+    CONN,
+};
+
+use super::bookmark::Position;
+
+/// 单次搜索返回的最大结果数
+const MAX_RESULT: usize = 200;
+
+/// 书内搜索返回的最大匹配项数
+pub const MAX_MATCHES: usize = 1000;
+
+/// trigram 分词器要求关键字至少包含 3 个字符
+const MIN_MATCH_LEN: usize = 3;
+
+/// 片段中关键字前后保留的字符数
+const SNIPPET_CONTEXT: usize = 16;
+
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SearchResult {
+    /// 书本的唯一标识符
+    pub book_id: String,
+
+    /// 书本标题
+    pub title: String,
+
+    /// 章节索引(spine 中的索引)
+    pub chapter: usize,
+
+    /// 段落索引
+    pub paragraph: usize,
+
+    /// 命中内容的片段, 关键字使用 <mark> 标签包裹
+    pub snippet: String,
+}
+
+impl SearchResult {
+    /// 将书本的内容写入全文索引
+    ///
+    /// 参数 chapters 按 spine 顺序排列, 每个元素为该章节的段落列表
+    pub fn insert_book(book_id: &str, chapters: &[Vec<String>]) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+
+        let tx = conn.transaction()?;
+
+        {
+            let sql = "INSERT INTO book_search (
+                                book_id,
+                                chapter,
+                                paragraph,
+                                content
+                            ) VALUES (
+                                ?1, ?2, ?3, ?4
+                            );";
+            let mut stmt = tx.prepare(sql)?;
+
+            for (chapter, paragraphs) in chapters.iter().enumerate() {
+                for (paragraph, content) in paragraphs.iter().enumerate() {
+                    stmt.execute(params![book_id, chapter, paragraph, content])?;
+                }
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// 尚未建立全文索引的书本的 id
+    pub fn unindexed_books() -> Result<Vec<String>> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT id FROM book_info
+                        WHERE id NOT IN (SELECT DISTINCT book_id FROM book_search);";
+        let mut stmt = conn.prepare(sql)?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(ids)
+    }
+
+    /// 在所有书本中搜索关键字, 结果按相关度排序
+    pub fn search(key: &str) -> Result<Vec<SearchResult>> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // trigram 分词器无法匹配过短的关键字, 此时退化为逐行匹配
+        if key.chars().count() < MIN_MATCH_LEN {
+            return Self::search_by_like(key);
+        }
+
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT 
+                            book_search.book_id, 
+                            book_info.title, 
+                            book_search.chapter, 
+                            book_search.paragraph, 
+                            snippet(book_search, 3, '<mark>', '</mark>', '...', 32)
+                        FROM book_search 
+                        JOIN book_info ON book_info.id = book_search.book_id
+                        WHERE book_search MATCH ?1
+                        ORDER BY rank
+                        LIMIT ?2;";
+        // 使用短语查询, 避免关键字被解析为 FTS5 的查询语法
+        let phrase = format!("\"{}\"", key.replace('"', "\"\""));
+
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params![phrase, MAX_RESULT], |row| {
+            Ok(SearchResult {
+                book_id: row.get(0)?,
+                title: row.get(1)?,
+                chapter: row.get(2)?,
+                paragraph: row.get(3)?,
+                snippet: row.get(4)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<SearchResult>>>()?)
+    }
+
+    fn search_by_like(key: &str) -> Result<Vec<SearchResult>> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT 
+                            book_search.book_id, 
+                            book_info.title, 
+                            book_search.chapter, 
+                            book_search.paragraph, 
+                            book_search.content
+                        FROM book_search 
+                        JOIN book_info ON book_info.id = book_search.book_id
+                        WHERE book_search.content LIKE ?1 ESCAPE '\\'
+                        LIMIT ?2;";
+        let pattern = format!(
+            "%{}%",
+            key.replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params![pattern, MAX_RESULT], |row| {
+            let content: String = row.get(4)?;
+
+            Ok(SearchResult {
+                book_id: row.get(0)?,
+                title: row.get(1)?,
+                chapter: row.get(2)?,
+                paragraph: row.get(3)?,
+                snippet: snippet(&content, key),
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<Vec<SearchResult>>>()?)
+    }
+}
+
+/// 书内搜索的匹配选项
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SearchOption {
+    /// 忽略大小写
+    pub case_insensitive: bool,
+
+    /// 全词匹配
+    pub whole_word: bool,
+
+    /// 将关键字视为正则表达式
+    pub regex: bool,
+}
+
+impl SearchOption {
+    /// 根据匹配选项构建正则表达式
+    pub fn build(&self, key: &str) -> Result<Regex> {
+        let mut pattern = if self.regex {
+            key.to_string()
+        } else {
+            regex::escape(key)
+        };
+
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(self.case_insensitive)
+            .build()
+            .map_err(|err| Error::InvalidArgument(err.to_string()))
+    }
+}
+
+/// 在章节的段落中查找匹配项, 最多返回 limit 个
+///
+/// offset 为匹配项在段落中的 UTF-16 偏移量, 与前端字符串下标保持一致
+pub fn find_matches(
+    pattern: &Regex,
+    chapter: usize,
+    paragraphs: &[String],
+    limit: usize,
+) -> Vec<Position> {
+    let mut list = Vec::new();
+
+    for (paragraph, content) in paragraphs.iter().enumerate() {
+        for item in pattern.find_iter(content) {
+            if list.len() >= limit {
+                return list;
+            }
+
+            // 空匹配无法定位到具体内容, 直接跳过
+            if item.is_empty() {
+                continue;
+            }
+
+            list.push(Position {
+                chapter,
+                paragraph,
+                offset: content[..item.start()].encode_utf16().count(),
+            });
+        }
+    }
+
+    list
+}
+
+/// 截取关键字附近的内容并高亮关键字
+fn snippet(content: &str, key: &str) -> String {
+    let lower = content.to_lowercase();
+    let start = match lower.find(&key.to_lowercase()) {
+        // 转为小写后字节位置可能发生变化, 仅在长度一致时使用该位置
+        Some(start) if lower.len() == content.len() => start,
+        _ => return content.chars().take(SNIPPET_CONTEXT * 2).collect(),
+    };
+    let end = start + key.len();
+
+    let before = &content[..start];
+    let skip = before.chars().count().saturating_sub(SNIPPET_CONTEXT);
+    let prefix = if skip > 0 { "..." } else { "" };
+    let before = before.chars().skip(skip).collect::<String>();
+
+    let after = &content[end..];
+    let suffix = if after.chars().count() > SNIPPET_CONTEXT {
+        "..."
+    } else {
+        ""
+    };
+    let after = after.chars().take(SNIPPET_CONTEXT).collect::<String>();
+
+    format!(
+        "{}{}<mark>{}</mark>{}{}",
+        prefix,
+        before,
+        &content[start..end],
+        after,
+        suffix
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_matches, snippet, SearchOption, MAX_MATCHES};
+
+    #[test]
+    fn snippet_test() {
+        assert_eq!(snippet("你好世界", "世"), "你好<mark>世</mark>界");
+        assert_eq!(snippet("Hello World", "wo"), "Hello <mark>Wo</mark>rld");
+        assert_eq!(
+            snippet("abcdefghijklmnopqrstuvwxyz0123456789", "z"),
+            "...jklmnopqrstuvwxy<mark>z</mark>0123456789"
+        );
+    }
+
+    #[test]
+    fn find_matches_test() {
+        let paragraphs = vec!["The cat scattered".to_string(), "猫 Cat".to_string()];
+
+        let option = SearchOption::default();
+        let pattern = option.build("cat").unwrap();
+        let list = find_matches(&pattern, 2, &paragraphs, MAX_MATCHES);
+        assert_eq!(list.len(), 2);
+        assert_eq!((list[1].chapter, list[1].paragraph, list[1].offset), (2, 0, 9));
+
+        let option = SearchOption {
+            case_insensitive: true,
+            whole_word: true,
+            regex: false,
+        };
+        let pattern = option.build("cat").unwrap();
+        let list = find_matches(&pattern, 0, &paragraphs, MAX_MATCHES);
+        assert_eq!(list.len(), 2);
+        assert_eq!((list[1].paragraph, list[1].offset), (1, 2));
+
+        let option = SearchOption {
+            regex: true,
+            ..Default::default()
+        };
+        let pattern = option.build("c.t").unwrap();
+        assert_eq!(find_matches(&pattern, 0, &paragraphs, MAX_MATCHES).len(), 2);
+        assert_eq!(find_matches(&pattern, 0, &paragraphs, 1).len(), 1);
+    }
+}
@@ -0,0 +1,67 @@
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::error::Result;
+
+use super::bookinfo::BookInfo;
+
+/// 系列, 包含书库中属于该系列的全部书本
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct Series {
+    /// 系列名称, 名称只有大小写不同时视为同一系列
+    pub name: String,
+
+    /// 系列中的书本, 按序号排列
+    pub books: Vec<BookInfo>,
+}
+
+impl Series {
+    /// 获取全部系列, 按名称排序
+    pub fn get_series_list() -> Result<Vec<Series>> {
+        Ok(group(BookInfo::get_series_books()?))
+    }
+}
+
+// 将已按系列名称排序的书本分组
+fn group(books: Vec<BookInfo>) -> Vec<Series> {
+    let mut list: Vec<Series> = Vec::new();
+
+    for book in books {
+        match list.last_mut() {
+            Some(series) if series.name.to_lowercase() == book.series.to_lowercase() => {
+                series.books.push(book)
+            }
+            _ => list.push(Series {
+                name: book.series.clone(),
+                books: vec![book],
+            }),
+        }
+    }
+
+    list
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::bookinfo::BookInfo;
+
+    use super::group;
+
+    fn info(series: &str) -> BookInfo {
+        BookInfo {
+            series: series.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn group_test() {
+        let list = group(vec![info("Rust"), info("rust"), info("Tokio")]);
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "Rust");
+        assert_eq!(list[0].books.len(), 2);
+        assert_eq!(list[1].books.len(), 1);
+    }
+}
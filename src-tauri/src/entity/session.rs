@@ -0,0 +1,85 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{error::Result, CONN};
+
+/// 一次连续的阅读
+///
+/// 删除书本时保留阅读记录, 使每日统计与连续阅读天数保持不变
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ReadingSession {
+    /// 尚未保存的会话为 0
+    pub id: usize,
+
+    pub book_id: String,
+
+    #[ts(type = "number")]
+    pub start_time: u64,
+
+    /// 最后一次阅读活动的时间
+    #[ts(type = "number")]
+    pub end_time: u64,
+
+    /// 本次阅读访问过的章节, 按访问顺序排列
+    pub chapters: Vec<usize>,
+
+    /// 访问过的章节的总词数, 作为阅读词数的近似值
+    pub words: usize,
+}
+
+impl ReadingSession {
+    pub fn new(book_id: &str, time: u64) -> Self {
+        ReadingSession {
+            book_id: book_id.to_string(),
+            start_time: time,
+            end_time: time,
+            ..Default::default()
+        }
+    }
+
+    /// 阅读的时长, 单位为毫秒
+    pub fn duration(&self) -> u64 {
+        self.end_time.saturating_sub(self.start_time)
+    }
+
+    /// 保存会话, 第一次保存时分配 id
+    pub fn save_session(&mut self) -> Result<()> {
+        let conn = CONN.lock().unwrap();
+
+        let chapters = self
+            .chapters
+            .iter()
+            .map(|chapter| chapter.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+
+        if self.id == 0 {
+            let sql = "INSERT INTO reading_session (
+                                book_id,
+                                start_time,
+                                end_time,
+                                chapters,
+                                words
+                            ) VALUES (
+                                ?1, ?2, ?3, ?4, ?5
+                            );";
+            let params = params![
+                self.book_id,
+                self.start_time,
+                self.end_time,
+                chapters,
+                self.words
+            ];
+            conn.execute(sql, params)?;
+            self.id = conn.last_insert_rowid() as usize;
+        } else {
+            let sql = "UPDATE reading_session SET end_time = ?1, chapters = ?2, words = ?3
+                            WHERE id = ?4;";
+            conn.execute(sql, params![self.end_time, chapters, self.words, self.id])?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,193 @@
+use rusqlite::{params, ErrorCode, Params, Row};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    error::{Error, Result},
+    utils::common_utils::time_stamp,
+    CONN,
+};
+
+/// 用户创建的书架, 一本书可以放在多个书架中
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Shelf {
+    pub id: usize,
+
+    /// 书架名称, 不可重复
+    pub name: String,
+
+    #[ts(type = "number")]
+    pub create_time: u64,
+
+    /// 书架中的书本数量
+    pub book_count: usize,
+}
+
+impl Shelf {
+    /// 获取全部书架, 按名称排序
+    pub fn get_shelf_list() -> Result<Vec<Shelf>> {
+        let sql = "SELECT shelf.id, shelf.name, shelf.create_time, count(book_shelf.book_id)
+                        FROM shelf LEFT JOIN book_shelf ON shelf.id = book_shelf.shelf_id
+                        GROUP BY shelf.id ORDER BY shelf.name;";
+
+        Shelf::query_shelf_list(sql, [])
+    }
+
+    /// 获取书本所在的全部书架
+    pub fn get_book_shelves(book_id: &str) -> Result<Vec<Shelf>> {
+        let sql = "SELECT shelf.id, shelf.name, shelf.create_time, count(book_shelf.book_id)
+                        FROM shelf LEFT JOIN book_shelf ON shelf.id = book_shelf.shelf_id
+                        WHERE shelf.id IN (SELECT shelf_id FROM book_shelf WHERE book_id = ?1)
+                        GROUP BY shelf.id ORDER BY shelf.name;";
+
+        Shelf::query_shelf_list(sql, [book_id])
+    }
+
+    fn query_shelf_list<P>(sql: &str, params: P) -> Result<Vec<Shelf>>
+    where
+        P: Params,
+    {
+        let conn = CONN.lock().unwrap();
+
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, Shelf::from_row)?;
+
+        let mut list = Vec::new();
+        for row in rows {
+            list.push(row?);
+        }
+
+        Ok(list)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Shelf> {
+        Ok(Shelf {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            create_time: row.get(2)?,
+            book_count: row.get(3)?,
+        })
+    }
+
+    /// 创建书架, 名称为空或已存在时返回 InvalidArgument
+    pub fn create_shelf(name: &str) -> Result<Shelf> {
+        let name = check_name(name)?;
+        let conn = CONN.lock().unwrap();
+
+        let create_time = time_stamp();
+        let sql = "INSERT INTO shelf (name, create_time) VALUES (?1, ?2);";
+        conn.execute(sql, params![name, create_time])
+            .map_err(|err| name_error(err, &name))?;
+
+        Ok(Shelf {
+            id: conn.last_insert_rowid() as usize,
+            name,
+            create_time,
+            book_count: 0,
+        })
+    }
+
+    /// 重命名书架, 书架不存在时返回 NotFound
+    pub fn rename_shelf(id: usize, name: &str) -> Result<()> {
+        let name = check_name(name)?;
+        let conn = CONN.lock().unwrap();
+
+        let sql = "UPDATE shelf SET name = ?1 WHERE id = ?2;";
+        match conn
+            .execute(sql, params![name, id])
+            .map_err(|err| name_error(err, &name))?
+        {
+            0 => Err(Error::NotFound("Shelf".to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    /// 删除书架, 书架中的书本不会被删除
+    pub fn remove_shelf(id: usize) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        if tx.execute("DELETE FROM shelf WHERE id = ?1;", [id])? == 0 {
+            return Err(Error::NotFound("Shelf".to_string()));
+        }
+        tx.execute("DELETE FROM book_shelf WHERE shelf_id = ?1;", [id])?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// 将书本添加到书架, 已在书架中的书本保持不变
+    ///
+    /// 书架或任一书本不存在时返回 NotFound, 不添加任何书本
+    pub fn add_books(id: usize, book_ids: &[String]) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let sql = "SELECT count(*) FROM shelf WHERE id = ?1;";
+        if tx.query_row(sql, [id], |row| row.get::<usize, usize>(0))? == 0 {
+            return Err(Error::NotFound("Shelf".to_string()));
+        }
+
+        for book_id in book_ids {
+            let sql = "SELECT count(*) FROM book_info WHERE id = ?1;";
+            if tx.query_row(sql, [book_id], |row| row.get::<usize, usize>(0))? == 0 {
+                return Err(Error::NotFound("Book".to_string()));
+            }
+
+            let sql = "INSERT OR IGNORE INTO book_shelf (book_id, shelf_id) VALUES (?1, ?2);";
+            tx.execute(sql, params![book_id, id])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// 将书本移出书架
+    pub fn remove_books(id: usize, book_ids: &[String]) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        for book_id in book_ids {
+            let sql = "DELETE FROM book_shelf WHERE book_id = ?1 AND shelf_id = ?2;";
+            tx.execute(sql, params![book_id, id])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+// 去除名称两端的空白, 名称为空时返回 InvalidArgument
+fn check_name(name: &str) -> Result<String> {
+    match name.trim() {
+        "" => Err(Error::InvalidArgument(
+            "Shelf name cannot be empty".to_string(),
+        )),
+        name => Ok(name.to_string()),
+    }
+}
+
+// 名称重复时转换为 InvalidArgument
+fn name_error(err: rusqlite::Error, name: &str) -> Error {
+    match err.sqlite_error_code() {
+        Some(ErrorCode::ConstraintViolation) => {
+            Error::InvalidArgument(format!("Shelf {} already exists", name))
+        }
+        _ => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::check_name;
+
+    #[test]
+    fn name_test() {
+        assert_eq!(check_name("  Rust  ").unwrap(), "Rust");
+        assert!(check_name(" ").is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use rusqlite::{Connection, Params, Row};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    error::{Error, Result},
+    CONN,
+};
+
+/// 统计的周期, 周从星期一开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    /// 计算阅读开始时间所在周期的起始日期, 使用本地时区
+    fn start_date(&self) -> &'static str {
+        match self {
+            Period::Day => "date(start_time / 1000, 'unixepoch', 'localtime')",
+            Period::Week => {
+                "date(start_time / 1000, 'unixepoch', 'localtime', 'weekday 0', '-6 days')"
+            }
+        }
+    }
+
+    /// 最近 count 个周期中第一个周期的起始日期
+    fn first_date(&self, count: u32) -> String {
+        let count = count.max(1) - 1;
+
+        match self {
+            Period::Day => format!("date('now', 'localtime', '-{} days')", count),
+            Period::Week => format!(
+                "date('now', 'localtime', 'weekday 0', '-6 days', '-{} days')",
+                count * 7
+            ),
+        }
+    }
+}
+
+/// 一个周期内的阅读总量
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct ReadingTotal {
+    /// 周期的起始日期, 格式为 YYYY-MM-DD
+    pub date: String,
+
+    /// 阅读时长, 单位为毫秒
+    #[ts(type = "number")]
+    pub duration: u64,
+
+    /// 阅读的词数, 为近似值
+    pub words: usize,
+
+    /// 阅读的次数
+    pub sessions: usize,
+}
+
+/// 连续阅读的天数
+#[derive(Debug, Default, PartialEq, Eq, Serialize, TS)]
+#[ts(export)]
+pub struct ReadingStreak {
+    /// 截至今天的连续天数, 今天尚未阅读时截至昨天
+    pub current: usize,
+
+    /// 最长的连续天数
+    pub longest: usize,
+}
+
+/// 单本书的阅读统计
+#[derive(Debug, Serialize, TS)]
+#[ts(export)]
+pub struct BookStats {
+    pub book_id: String,
+
+    pub title: String,
+
+    /// 阅读时长, 单位为毫秒
+    #[ts(type = "number")]
+    pub duration: u64,
+
+    pub words: usize,
+
+    pub sessions: usize,
+
+    /// 阅读进度百分比
+    pub percentage: f64,
+
+    /// 预计剩余的阅读时长, 单位为毫秒, 无法估计时为 null
+    #[ts(type = "number | null")]
+    pub remaining: Option<u64>,
+}
+
+impl ReadingTotal {
+    /// 获取最近 count 个周期的阅读总量, 按日期排序, 没有阅读的周期不包含在结果中
+    pub fn get_totals(period: Period, count: u32) -> Result<Vec<ReadingTotal>> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = format!(
+            "SELECT {0} AS period, sum(end_time - start_time), sum(words), count(*)
+                FROM reading_session WHERE {0} >= {1}
+                GROUP BY period ORDER BY period;",
+            period.start_date(),
+            period.first_date(count)
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok(ReadingTotal {
+                date: row.get(0)?,
+                duration: row.get(1)?,
+                words: row.get(2)?,
+                sessions: row.get(3)?,
+            })
+        })?;
+
+        let mut list = Vec::new();
+        for row in rows {
+            list.push(row?);
+        }
+
+        Ok(list)
+    }
+}
+
+impl ReadingStreak {
+    pub fn get_streak() -> Result<ReadingStreak> {
+        let conn = CONN.lock().unwrap();
+
+        // 使用儒略日将日期转换为连续的整数
+        let sql = "SELECT DISTINCT CAST(julianday(date(start_time / 1000, 'unixepoch', 'localtime')) AS INTEGER) AS day
+                        FROM reading_session ORDER BY day;";
+        let mut stmt = conn.prepare(sql)?;
+        let days = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+
+        let sql = "SELECT CAST(julianday(date('now', 'localtime')) AS INTEGER);";
+        let today = conn.query_row(sql, [], |row| row.get(0))?;
+
+        Ok(streak(&days, today))
+    }
+}
+
+// 根据已排序且不重复的阅读日期计算连续天数
+fn streak(days: &[i64], today: i64) -> ReadingStreak {
+    let mut result = ReadingStreak::default();
+    let mut run = 0;
+
+    for (index, day) in days.iter().enumerate() {
+        run = match index > 0 && days[index - 1] + 1 == *day {
+            true => run + 1,
+            false => 1,
+        };
+        result.longest = result.longest.max(run);
+
+        if *day == today || *day == today - 1 {
+            result.current = run;
+        }
+    }
+
+    result
+}
+
+impl BookStats {
+    /// 获取单本书的阅读统计
+    pub fn get_book_stats(id: &str) -> Result<BookStats> {
+        let sql = format!(
+            "{} WHERE book_info.id = ?1 GROUP BY book_info.id;",
+            STATS_SQL
+        );
+
+        BookStats::query_stats_list(&CONN.lock().unwrap(), &sql, [id])?
+            .pop()
+            .ok_or(Error::NotFound("Book".to_string()))
+    }
+
+    /// 获取全部读过的书本的阅读统计, 按阅读时长倒序排列
+    ///
+    /// 书本移除后阅读记录仍计入阅读总量与连续天数, 但不再出现在列表中
+    pub fn get_stats_list() -> Result<Vec<BookStats>> {
+        BookStats::query_read_list(&CONN.lock().unwrap())
+    }
+
+    fn query_read_list(conn: &Connection) -> Result<Vec<BookStats>> {
+        let sql = format!(
+            "{} GROUP BY book_info.id HAVING count(reading_session.id) > 0 ORDER BY 3 DESC;",
+            STATS_SQL
+        );
+
+        BookStats::query_stats_list(conn, &sql, [])
+    }
+
+    fn query_stats_list<P>(conn: &Connection, sql: &str, params: P) -> Result<Vec<BookStats>>
+    where
+        P: Params,
+    {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, BookStats::from_row)?;
+
+        let mut list = Vec::new();
+        for row in rows {
+            list.push(row?);
+        }
+
+        Ok(list)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<BookStats> {
+        let duration = row.get(2)?;
+        let percentage = row.get(5)?;
+
+        Ok(BookStats {
+            book_id: row.get(0)?,
+            title: row.get(1)?,
+            duration,
+            words: row.get(3)?,
+            sessions: row.get(4)?,
+            percentage,
+            remaining: estimate_remaining(duration, percentage),
+        })
+    }
+}
+
+/// 书本阅读统计的查询, 使用时追加筛选与分组条件
+///
+/// 以 book_info 为主表, 已移除书本遗留的阅读记录不会被统计
+const STATS_SQL: &str = "SELECT book_info.id, title,
+                                coalesce(sum(end_time - start_time), 0),
+                                coalesce(sum(words), 0),
+                                count(reading_session.id),
+                                coalesce(max(percentage), 0)
+                            FROM book_info
+                            LEFT JOIN reading_session ON book_info.id = reading_session.book_id
+                            LEFT JOIN reading_progress ON book_info.id = reading_progress.book_id";
+
+// 按已读部分的速度估计剩余的阅读时长
+fn estimate_remaining(duration: u64, percentage: f64) -> Option<u64> {
+    if duration == 0 || percentage <= 0.0 {
+        return None;
+    }
+    if percentage >= 100.0 {
+        return Some(0);
+    }
+
+    Some((duration as f64 * (100.0 - percentage) / percentage) as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use rusqlite::Connection;
+
+    use super::{estimate_remaining, streak, BookStats, ReadingStreak};
+
+    #[test]
+    fn streak_test() {
+        assert_eq!(streak(&[], 10), ReadingStreak::default());
+
+        let days = [1, 2, 3, 4, 7, 8, 9];
+        assert_eq!(
+            streak(&days, 9),
+            ReadingStreak {
+                current: 3,
+                longest: 4
+            }
+        );
+        // 今天尚未阅读时保留昨天的连续天数
+        assert_eq!(streak(&days, 10).current, 3);
+        assert_eq!(streak(&days, 11).current, 0);
+    }
+
+    #[test]
+    fn remaining_test() {
+        assert_eq!(estimate_remaining(0, 50.0), None);
+        assert_eq!(estimate_remaining(1000, 0.0), None);
+        assert_eq!(estimate_remaining(1000, 25.0), Some(3000));
+        assert_eq!(estimate_remaining(1000, 100.0), Some(0));
+    }
+
+    #[test]
+    fn removed_book_test() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE book_info (id TEXT PRIMARY KEY, title TEXT);
+            CREATE TABLE reading_session (id INTEGER PRIMARY KEY, book_id TEXT,
+                start_time INTEGER, end_time INTEGER, words INTEGER);
+            CREATE TABLE reading_progress (book_id TEXT, percentage REAL);
+            INSERT INTO book_info VALUES ('kept', 'Kept'), ('unread', 'Unread');
+            INSERT INTO reading_session (book_id, start_time, end_time, words)
+                VALUES ('kept', 0, 1000, 10), ('removed', 0, 5000, 50);",
+        )
+        .unwrap();
+
+        // 已移除书本(removed)的阅读记录与未读的书本都不出现在列表中
+        let list = BookStats::query_read_list(&conn).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].book_id, "kept");
+        assert_eq!(list[0].duration, 1000);
+    }
+}
@@ -0,0 +1,123 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    error::{Error, Result},
+    CONN,
+};
+
+/// 书本的标签, 标签不需要单独创建, 没有书本使用时自动消失
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Tag {
+    pub name: String,
+
+    /// 使用该标签的书本数量
+    pub book_count: usize,
+}
+
+impl Tag {
+    /// 获取全部标签, 按名称排序
+    pub fn get_tag_list() -> Result<Vec<Tag>> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT tag, count(book_id) FROM book_tag GROUP BY tag ORDER BY tag;";
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Tag {
+                name: row.get(0)?,
+                book_count: row.get(1)?,
+            })
+        })?;
+
+        let mut list = Vec::new();
+        for row in rows {
+            list.push(row?);
+        }
+
+        Ok(list)
+    }
+
+    /// 获取书本的全部标签, 按名称排序
+    pub fn get_book_tags(book_id: &str) -> Result<Vec<String>> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT tag FROM book_tag WHERE book_id = ?1 ORDER BY tag;";
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([book_id], |row| row.get(0))?;
+
+        let mut list = Vec::new();
+        for row in rows {
+            list.push(row?);
+        }
+
+        Ok(list)
+    }
+
+    /// 使用新的标签替换书本原有的全部标签, 书本不存在时返回 NotFound
+    pub fn set_book_tags(book_id: &str, tags: &[String]) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let sql = "SELECT count(*) FROM book_info WHERE id = ?1;";
+        if tx.query_row(sql, [book_id], |row| row.get::<usize, usize>(0))? == 0 {
+            return Err(Error::NotFound("Book".to_string()));
+        }
+
+        tx.execute("DELETE FROM book_tag WHERE book_id = ?1;", [book_id])?;
+        for tag in normalize_tags(tags) {
+            let sql = "INSERT INTO book_tag (book_id, tag) VALUES (?1, ?2);";
+            tx.execute(sql, params![book_id, tag])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// 重命名标签, 新的标签已存在时合并两个标签, 新的名称为空时删除该标签
+    pub fn rename_tag(name: &str, new_name: &str) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        let new_name = new_name.trim();
+        let sql = "INSERT OR IGNORE INTO book_tag (book_id, tag)
+                        SELECT book_id, ?2 FROM book_tag WHERE tag = ?1;";
+        if !new_name.is_empty() {
+            tx.execute(sql, [name, new_name])?;
+        }
+        if new_name != name {
+            tx.execute("DELETE FROM book_tag WHERE tag = ?1;", [name])?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+// 去除标签两端的空白, 并移除空标签与重复的标签
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut list = tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<String>>();
+    list.sort();
+    list.dedup();
+
+    list
+}
+
+#[cfg(test)]
+mod test {
+    use super::normalize_tags;
+
+    #[test]
+    fn normalize_test() {
+        let tags = ["rust ", "", "  ", "async", "rust"].map(|tag| tag.to_string());
+
+        assert_eq!(normalize_tags(&tags), vec!["async", "rust"]);
+    }
+}
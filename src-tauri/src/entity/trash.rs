@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::{bookinfo::BookInfo, bookmark::BookMark, progress::ReadingProgress};
+
+/// 回收站中的书本
+///
+/// 删除时保存书本的全部数据, 恢复时重新写入数据库
+#[derive(Debug, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TrashItem {
+    pub info: BookInfo,
+
+    pub marks: Vec<BookMark>,
+
+    pub progress: Option<ReadingProgress>,
+
+    /// 书本所在书架的 id, 恢复时忽略已删除的书架
+    #[serde(default)]
+    pub shelves: Vec<usize>,
+
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// 删除的时间
+    #[ts(type = "number")]
+    pub deleted_time: u64,
+}
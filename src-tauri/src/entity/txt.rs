@@ -0,0 +1,246 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use log::warn;
+use regex::Regex;
+
+use crate::{
+    error::Result,
+    utils::{
+        config_utils::read_config,
+        content_utils::{decode_text, escape_html},
+    },
+};
+
+use super::{book::Book, bookinfo::BookInfo, catalog::CatalogItem};
+
+/// 未识别到章节标题时, 每个章节包含的最大行数
+const LINES_PER_PAGE: usize = 200;
+
+/// 章节标题的最大字符数, 超过该长度的行不会被识别为标题
+const MAX_TITLE_LEN: usize = 50;
+
+#[derive(Debug)]
+pub struct Txt {
+    /// 书本的公共信息，可以从外部直接获取
+    pub info: BookInfo,
+
+    /// 章节列表
+    chapters: Vec<Chapter>,
+
+    /// 当前页面
+    current_page: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Chapter {
+    /// 章节标题
+    title: String,
+
+    /// 章节内容, 每一行为一个段落
+    lines: Vec<String>,
+}
+
+impl Txt {
+    /// 打开书库中保存的 txt 文件, 章节名使用书本信息中的标题
+    pub fn open(info: BookInfo) -> Result<Self> {
+        let text = decode_text(&fs::read(&info.file_path)?);
+
+        Ok(Self::from_text(info, &text))
+    }
+
+    /// 使用已解码的文本构建 Txt
+    pub fn from_text(info: BookInfo, text: &str) -> Self {
+        let chapters = split_chapters(text, &info.title, &chapter_patterns());
+
+        Txt {
+            info,
+            chapters,
+            current_page: 0,
+        }
+    }
+}
+
+impl Book for Txt {
+    fn info(&self) -> &BookInfo {
+        &self.info
+    }
+
+    fn set_info(&mut self, info: BookInfo) {
+        self.info = info;
+    }
+
+    fn get_catalog(&self) -> Vec<CatalogItem> {
+        self.chapters
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| CatalogItem {
+                label: chapter.title.clone(),
+                level: 1,
+                index,
+                target: index,
+                anchor: None,
+                play_order: index + 1,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn page_count(&self) -> usize {
+        self.chapters.len()
+    }
+
+    fn current_page(&self) -> usize {
+        self.current_page
+    }
+
+    fn set_current_page(&mut self, num: usize) -> bool {
+        if num < self.chapters.len() {
+            self.current_page = num;
+
+            return true;
+        }
+
+        false
+    }
+
+    /// 获取当前章节的内容, 标题为 h2 元素, 每一行为一个 p 元素
+    fn get_current_page(&mut self) -> String {
+        let chapter = match self.chapters.get(self.current_page) {
+            Some(chapter) => chapter,
+            None => return String::new(),
+        };
+
+        let mut content = format!("<h2>{}</h2>", escape_html(&chapter.title));
+        for line in &chapter.lines {
+            content.push_str(&format!("<p>{}</p>", escape_html(line)));
+        }
+
+        content
+    }
+
+    /// 获取指定章节的段落文本, 第一个段落为章节标题
+    fn get_page_text(&mut self, index: usize) -> Vec<String> {
+        match self.chapters.get(index) {
+            Some(chapter) => {
+                let mut list = vec![chapter.title.clone()];
+                list.extend(chapter.lines.iter().cloned());
+                list
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// txt 文件没有内嵌资源
+    fn get_resource(&mut self, _path: &Path) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// txt 文件没有样式表
+    fn get_css(&mut self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+}
+
+/// 从配置中读取章节标题的匹配规则, 忽略无法解析的规则
+pub fn chapter_patterns() -> Vec<Regex> {
+    read_config()
+        .setting
+        .chapter_patterns
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(regex) => Some(regex),
+            Err(err) => {
+                warn!("章节标题规则 {} 无效: {}", pattern, err);
+                None
+            }
+        })
+        .collect()
+}
+
+/// 按章节标题拆分文本
+///
+/// 第一个标题之前的内容作为一个以书名为标题的章节;
+/// 若全文没有匹配到任何标题, 则按固定行数拆分章节
+fn split_chapters(text: &str, title: &str, patterns: &[Regex]) -> Vec<Chapter> {
+    let lines = text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty());
+
+    let mut chapters = Vec::new();
+    let mut current = Chapter {
+        title: title.to_string(),
+        lines: Vec::new(),
+    };
+    let mut matched = false;
+
+    for line in lines {
+        let is_title = line.chars().count() <= MAX_TITLE_LEN
+            && patterns.iter().any(|pattern| pattern.is_match(line));
+
+        if is_title {
+            matched = true;
+
+            let previous = std::mem::replace(
+                &mut current,
+                Chapter {
+                    title: line.to_string(),
+                    lines: Vec::new(),
+                },
+            );
+            if !previous.lines.is_empty() || previous.title != title {
+                chapters.push(previous);
+            }
+        } else {
+            current.lines.push(line.to_string());
+        }
+    }
+
+    if matched {
+        chapters.push(current);
+        return chapters;
+    }
+
+    // 没有识别到标题, 按固定行数拆分
+    let total = current.lines.len().div_ceil(LINES_PER_PAGE).max(1);
+    let mut lines = current.lines.into_iter();
+    for index in 0..total {
+        chapters.push(Chapter {
+            title: format!("{} ({}/{})", title, index + 1, total),
+            lines: lines.by_ref().take(LINES_PER_PAGE).collect(),
+        });
+    }
+
+    chapters
+}
+
+#[cfg(test)]
+mod test {
+    use regex::Regex;
+
+    use super::{split_chapters, LINES_PER_PAGE};
+
+    #[test]
+    fn split_test() {
+        let patterns = vec![
+            Regex::new(r"^第[0-9零一二三四五六七八九十百千万]+章").unwrap(),
+            Regex::new(r"^(?i:chapter)\s*[0-9]+\b").unwrap(),
+        ];
+        let text = "简介\r\n\r\n第一章 开始\n　　正文一\n第二章 继续\n正文二\nCHAPTER 3\n正文三\n";
+
+        let chapters = split_chapters(text, "书名", &patterns);
+        let titles: Vec<&str> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["书名", "第一章 开始", "第二章 继续", "CHAPTER 3"]);
+        assert_eq!(chapters[1].lines, vec!["正文一"]);
+    }
+
+    #[test]
+    fn split_without_title_test() {
+        let text = "行\n".repeat(LINES_PER_PAGE + 1);
+
+        let chapters = split_chapters(&text, "书名", &[]);
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[1].title, "书名 (2/2)");
+        assert_eq!(chapters[1].lines.len(), 1);
+    }
+}
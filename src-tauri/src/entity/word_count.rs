@@ -0,0 +1,79 @@
+use std::ops::AddAssign;
+
+use rusqlite::params;
+
+use crate::{error::Result, utils::config_utils::Setting, CONN};
+
+/// 章节的字数, 中文与日文按字符计数, 其他文字按单词计数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WordCount {
+    /// 中日文以外的单词数
+    pub words: usize,
+
+    /// 中日文的字符数
+    pub characters: usize,
+}
+
+impl WordCount {
+    /// 单词数与字符数的总和
+    pub fn total(&self) -> usize {
+        self.words + self.characters
+    }
+
+    /// 按设置中的阅读速度估计阅读时间, 单位为分钟, 不足一分钟时按一分钟计算
+    pub fn minutes(&self, setting: &Setting) -> usize {
+        let minutes = self.words as f64 / setting.reading_speed.max(1) as f64
+            + self.characters as f64 / setting.cjk_reading_speed.max(1) as f64;
+
+        minutes.ceil() as usize
+    }
+
+    /// 获取书本各章节的字数, 按章节顺序排列, 没有缓存时返回空列表
+    pub fn get_book_counts(book_id: &str) -> Result<Vec<WordCount>> {
+        let conn = CONN.lock().unwrap();
+
+        let sql = "SELECT words, characters FROM chapter_words WHERE book_id = ?1
+                        ORDER BY chapter;";
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([book_id], |row| {
+            Ok(WordCount {
+                words: row.get(0)?,
+                characters: row.get(1)?,
+            })
+        })?;
+
+        let mut list = Vec::new();
+        for row in rows {
+            list.push(row?);
+        }
+
+        Ok(list)
+    }
+
+    /// 保存书本各章节的字数, 替换原有的缓存
+    pub fn save_book_counts(book_id: &str, counts: &[WordCount]) -> Result<()> {
+        let mut conn = CONN.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM chapter_words WHERE book_id = ?1;", [book_id])?;
+        for (chapter, count) in counts.iter().enumerate() {
+            let sql = "INSERT INTO chapter_words (book_id, chapter, words, characters)
+                            VALUES (?1, ?2, ?3, ?4);";
+            tx.execute(
+                sql,
+                params![book_id, chapter, count.words, count.characters],
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+impl AddAssign for WordCount {
+    fn add_assign(&mut self, other: Self) {
+        self.words += other.words;
+        self.characters += other.characters;
+    }
+}
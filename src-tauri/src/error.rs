@@ -0,0 +1,107 @@
+//! 程序统一使用的错误类型
+use std::{fmt::Display, io};
+
+use rusqlite::Error as SqlError;
+
+use crate::entity::notification::{Notification, NotificationType};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// 文件读写错误
+    Io(io::Error),
+
+    /// 数据库错误
+    Sql(SqlError),
+
+    /// 书本文件无法解析
+    Epub(String),
+
+    /// 图片无法解析或保存
+    Image(String),
+
+    /// 配置文件无法解析或保存
+    Config(String),
+
+    /// 请求的数据不存在, 参数为数据的名称
+    NotFound(String),
+
+    /// 请求的参数不合法
+    InvalidArgument(String),
+
+    /// 当前没有打开的书本
+    NoBookOpen,
+
+    /// 相同的任务正在执行, 参数为任务的名称
+    Busy(String),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "IO error: {}", err),
+            Error::Sql(err) => write!(f, "Database error: {}", err),
+            Error::Epub(msg) => write!(f, "Failed to parse book: {}", msg),
+            Error::Image(msg) => write!(f, "Failed to process image: {}", msg),
+            Error::Config(msg) => write!(f, "Config error: {}", msg),
+            Error::NotFound(name) => write!(f, "{} not found", name),
+            Error::InvalidArgument(msg) => write!(f, "Invalid argument: {}", msg),
+            Error::NoBookOpen => write!(f, "Has not open any book"),
+            Error::Busy(name) => write!(f, "{} is already running", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// 查询不到数据时转换为 NotFound, 其余错误保持为 Sql
+impl From<SqlError> for Error {
+    fn from(err: SqlError) -> Self {
+        match err {
+            SqlError::QueryReturnedNoRows => Error::NotFound("Record".to_string()),
+            err => Error::Sql(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::InvalidArgument(err.to_string())
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(err: image::ImageError) -> Self {
+        Error::Image(err.to_string())
+    }
+}
+
+impl From<serde_yml::Error> for Error {
+    fn from(err: serde_yml::Error) -> Self {
+        Error::Config(err.to_string())
+    }
+}
+
+impl From<&Error> for Notification {
+    fn from(err: &Error) -> Self {
+        let (r#type, title) = match err {
+            Error::NotFound(_) | Error::InvalidArgument(_) | Error::Busy(_) => {
+                (NotificationType::Warn, "Warn")
+            }
+            _ => (NotificationType::Err, "Error"),
+        };
+
+        Notification {
+            r#type,
+            title: title.to_string(),
+            msg: err.to_string(),
+        }
+    }
+}
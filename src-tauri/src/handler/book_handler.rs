@@ -1,278 +1,486 @@
 use std::{
-    fs::{create_dir_all, File},
-    io::{BufReader, Read, Write},
+    collections::HashMap,
+    fs,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, Mutex,
+    },
+    thread,
+    time::Duration,
 };
 
-use epub::doc::EpubDoc;
-use log::{error, warn};
-use rusqlite::Error as SqlError;
-use serde_json::json;
+use log::{info, warn};
+use notify_debouncer_mini::{notify::RecommendedWatcher, Debouncer};
+use tauri::{AppHandle, Emitter};
 
 use crate::{
     entity::{
-        bookinfo::BookInfo,
-        epub::Epub,
-        notification::{Notification, NotificationType},
+        book,
+        bookinfo::{BookInfo, BookMetadata},
+        bookmark::{BookMark, Position},
+        progress::ReadingProgress,
+        response::{OpenedBook, Response},
+        search::SearchResult,
+        shelf::Shelf,
+        tag::Tag,
+        trash::TrashItem,
+        word_count::WordCount,
     },
+    error::{Error, Result},
     utils::{
-        common_utils::{json_to_string, time_stamp},
+        common_utils::time_stamp,
         config_utils::read_config,
+        cover_utils::{book_images, replace_cover, thumbnail_paths},
+        import_utils::{count_book, expand_paths, import_books, index_book},
+        opf_utils::write_metadata,
+        session_utils::{end_session, start_session},
+        trash_utils::{clear_trash, move_to_trash, remove_files, restore_from_trash, trash_list},
+        watch_utils::{missing_notification, scan_folders, watch_folders, ScanReport},
     },
 };
 
-use super::CURRENT_BOOK;
+use super::{with_book, CURRENT_BOOK};
 
 /// 获取书籍详情
 ///
 /// 参数: id
 ///
-/// 返回一个Json Object:
-/// {
-///     "exist": boolean,
-///     "info": string,
-///     "msg": string
-/// }
+/// 返回 Response<BookInfo>
 #[tauri::command]
-pub fn book_detail(id: &str) -> String {
-    let result;
-
-    match BookInfo::get_specific_info(id) {
-        Ok(info) => {
-            result = json!({
-                "exist": true,
-                "info": info,
-            })
-        }
-        Err(SqlError::QueryReturnedNoRows) => {
-            let msg = Notification {
-                r#type: NotificationType::Warn,
-                title: "Warn".to_string(),
-                msg: "No such book".to_string(),
-            };
-
-            result = json!({
-                "exist": false,
-                "msg": msg,
-            })
-        }
-        Err(err) => {
-            let msg = Notification {
-                r#type: NotificationType::Err,
-                title: "Error".to_string(),
-                msg: "An error occurred while getting info".to_string(),
-            };
-
-            result = json!({
-                "exist": false,
-                "msg": msg,
-            });
-
-            warn!(
-                "查询数据时发生了错误: {:?}",
-                err.sqlite_error_code().unwrap()
-            );
-        }
-    }
-
-    json_to_string(&result)
+pub fn book_detail(id: &str) -> Response<BookInfo> {
+    BookInfo::get_specific_info(id).into()
 }
 
 /// 打开书籍
 ///
 /// 参数: id
 ///
-/// 返回一个Json Object:
-/// {
-///     "content": string,
-///     "success": boolean,
-///     "msg": string
-/// }
+/// 返回 Response<OpenedBook>
 #[tauri::command]
-pub fn open_book(id: &str) -> String {
-    let result;
-
-    match BookInfo::get_specific_info(id) {
-        Ok(mut info) => {
-            let mut book = Epub::new(&info.file_path);
-
-            info.last_open = time_stamp(); // 更新最后一次打开时间(time_stamp)
-            book.info = info.clone(); // 同步bookinfo
-            
-            {
-                // 需要在代码块中修改资源, 并在块结束时释放资源, 防止后续的死锁问题
-                let mut container = CURRENT_BOOK.lock().unwrap();
-                *container = Some(book);
-            }
+pub fn open_book(id: &str) -> Response<OpenedBook> {
+    open(id).into()
+}
 
-            BookInfo::update_info(&info); // 保存更新后的信息
+fn open(id: &str) -> Result<OpenedBook> {
+    let mut info = BookInfo::get_specific_info(id)?;
+    info.last_open = time_stamp(); // 更新最后一次打开时间(time_stamp)
+    let mut book = book::open(&info)?;
+
+    // 恢复上一次的阅读位置, 章节不存在时从头开始阅读
+    let mut position = match ReadingProgress::get_progress(&info.id) {
+        Ok(progress) => progress.position,
+        Err(_) => Position::default(),
+    };
+    if !book.set_current_page(position.chapter) {
+        position = Position::default();
+    }
 
-            result = json!({
-                "success": true,
-                "content": CURRENT_BOOK.lock().unwrap().as_mut().unwrap().get_current_page(),
-            });
-        }
-        // 查询不到指定数据
-        Err(SqlError::QueryReturnedNoRows) => {
-            let msg = Notification {
-                r#type: NotificationType::Err,
-                title: "Error".to_string(),
-                msg: "No such book".to_string(),
-            };
-
-            result = json!({
-                "success": false,
-                "msg": msg,
-            });
-        }
-        Err(err) => {
-            error!(
-                "查询数据时发生了错误: {:?}",
-                err.sqlite_error_code().unwrap()
-            );
-            panic!("{}", err);
-        }
+    // 导入于字数统计之前的书本在第一次打开时于后台统计
+    let cached = WordCount::get_book_counts(&info.id).map_or(0, |counts| counts.len());
+    if cached != book.page_count() {
+        let info = info.clone();
+        thread::spawn(move || count_book(&info));
+    }
+
+    let content = book.get_current_page();
+    start_session(book.as_mut());
+    {
+        // 需要在代码块中修改资源, 并在块结束时释放资源, 防止后续的死锁问题
+        let mut container = CURRENT_BOOK.lock().unwrap();
+        *container = Some(book);
+    }
+
+    // 保存更新后的信息, 失败时不影响阅读
+    if let Err(err) = BookInfo::update_info(&info) {
+        warn!("更新书本信息失败: {}", err);
     }
 
-    json_to_string(&result)
+    Ok(OpenedBook {
+        content,
+        progress: position,
+    })
 }
 
+/// 是否有正在进行的导入
+static IMPORTING: AtomicBool = AtomicBool::new(false);
+
+/// 取消导入的标记, 导入开始时重置
+static IMPORT_CANCEL: AtomicBool = AtomicBool::new(false);
+
+/// 当前的文件夹监视器, 替换或释放时停止监视旧的文件夹
+static WATCHER: LazyLock<Mutex<Option<Debouncer<RecommendedWatcher>>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+/// 同步监视的文件夹, 同一时间只进行一次
+static SYNC_LOCK: Mutex<()> = Mutex::new(());
+
+/// 同步时等待手动导入结束的检查间隔
+const IMPORT_WAIT: Duration = Duration::from_secs(1);
+
 /// 添加新书
 ///
-/// 参数: Vec<&str> 新书路径
+/// 参数: Vec<String> 新书或文件夹路径, 文件夹会被递归导入
+///
+/// 导入在后台线程中进行, 命令会立即返回.
+/// 导入进度通过 `import://progress` 事件发送, 载荷为 ImportProgress;
+/// 导入结束后发送 `import://done` 事件, 载荷为 ImportSummary
 ///
-/// 返回一个Json Array, 内容为 Notification
+/// 返回 Response<null>, 已有导入正在进行时返回提示信息
 #[tauri::command]
-pub fn update_new_book(paths: Vec<&str>) -> String {
-    let mut messages = Vec::new();
-    let mut title_list = Vec::new();
+pub fn update_new_book(app: AppHandle, paths: Vec<String>) -> Response<()> {
+    if IMPORTING.swap(true, Ordering::SeqCst) {
+        return Err(Error::Busy("Import".to_string())).into();
+    }
+    IMPORT_CANCEL.store(false, Ordering::SeqCst);
+
+    let paths = paths.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+    thread::spawn(move || {
+        let paths = expand_paths(&paths);
+        info!("开始导入 {} 个文件", paths.len());
 
-    for path in paths {
-        let path = Path::new(path);
+        let summary = import_books(&paths, &IMPORT_CANCEL, |progress| {
+            if let Err(err) = app.emit("import://progress", progress) {
+                warn!("发送导入进度失败: {}", err);
+            }
+        });
+
+        match summary.cancelled {
+            true => info!("导入被取消, 已处理 {} 个文件", summary.results.len()),
+            false => info!("导入结束, 已处理 {} 个文件", summary.results.len()),
+        }
+        IMPORTING.store(false, Ordering::SeqCst);
 
-        if !path.is_file() {
-            continue;
+        if let Err(err) = app.emit("import://done", summary) {
+            warn!("发送导入结果失败: {}", err);
         }
+    });
 
-        let mut info = BookInfo::new(path.to_path_buf());
-        let mut book = EpubDoc::new(path).unwrap();
+    Response::ok(())
+}
+
+/// 取消正在进行的导入
+///
+/// 正在导入的文件会继续完成, 剩余的文件不再导入
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn cancel_import() -> Response<()> {
+    IMPORT_CANCEL.store(true, Ordering::SeqCst);
+    Response::ok(())
+}
+
+/// 同步并监视文件夹, 在程序启动与监视的文件夹变化时调用
+///
+/// 新增的书本会被自动导入, 原始文件被移除的书本会被标记,
+/// 结果通过 `import://done` 事件发送
+pub fn watch_library(app: AppHandle, folders: Vec<String>) {
+    thread::spawn(move || {
+        let folders = folders.into_iter().map(PathBuf::from).collect::<Vec<_>>();
+        sync_library(&app, &folders);
+
+        let watcher = match folders.is_empty() {
+            true => None,
+            false => {
+                let (app, watched) = (app.clone(), folders.clone());
+                match watch_folders(&folders, move || sync_library(&app, &watched)) {
+                    Ok(watcher) => Some(watcher),
+                    Err(err) => {
+                        warn!("监视文件夹失败: {}", err);
+                        None
+                    }
+                }
+            }
+        };
 
-        info.last_open = time_stamp();
+        *WATCHER.lock().unwrap() = watcher;
+    });
+}
 
-        if !BookInfo::insert_info(&info) {
-            messages.push(Notification {
-                r#type: NotificationType::Warn,
-                title: "Warn".to_string(),
-                msg: format!("{} has been added.", info.title),
-            });
+fn sync_library(app: &AppHandle, folders: &[PathBuf]) {
+    if folders.is_empty() {
+        return;
+    }
 
-            continue;
-        } else {
-            title_list.push((&info.title).clone());
-        }
+    let _guard = SYNC_LOCK.lock().unwrap();
 
-        save_cover(&info, &mut book);
-        save_book(&info, path.to_str().unwrap());
-        save_resources(&info, &mut book);
+    // 等待手动导入结束, 同步期间手动导入返回 Busy
+    while IMPORTING.swap(true, Ordering::SeqCst) {
+        thread::sleep(IMPORT_WAIT);
+    }
+    match scan_folders(folders) {
+        Ok(report) if report.new_files.is_empty() && report.missing.is_empty() => {}
+        Ok(report) => import_report(app, &report),
+        Err(err) => warn!("同步监视的文件夹失败: {}", err),
     }
+    IMPORTING.store(false, Ordering::SeqCst);
+}
 
-    if title_list.len() != 0 {
-        messages.push(Notification {
-            r#type: NotificationType::Info,
-            title: "Info".to_string(),
-            msg: format!("Update [{}] success.", title_list.join(", ")),
-        })
+// 导入监视的文件夹中的新文件, 并发送导入结果
+fn import_report(app: &AppHandle, report: &ScanReport) {
+    info!(
+        "监视的文件夹中有 {} 个新文件, {} 本书的原始文件被移除",
+        report.new_files.len(),
+        report.missing.len()
+    );
+
+    let mut summary = import_books(&report.new_files, &AtomicBool::new(false), |_| {});
+    summary
+        .results
+        .extend(report.missing.iter().map(missing_notification));
+
+    if let Err(err) = app.emit("import://done", summary) {
+        warn!("发送导入结果失败: {}", err);
     }
+}
 
-    json_to_string(&messages)
+/// 修改书本的元数据
+///
+/// 参数: id, metadata 新的元数据, write_file 是否同时写入 epub 文件中的 OPF
+///
+/// 写入文件时, 第一次修改会将原始文件备份为 `<文件名>.orig`
+///
+/// 返回 Response<BookInfo>, 为修改后的书本信息
+#[tauri::command]
+pub fn update_book_metadata(
+    id: &str,
+    metadata: BookMetadata,
+    write_file: bool,
+) -> Response<BookInfo> {
+    update_metadata(id, &metadata, write_file).into()
 }
 
-fn save_cover(info: &BookInfo, book: &mut EpubDoc<BufReader<File>>) {
-    let (cover, mime) = book.get_cover().unwrap();
+fn update_metadata(id: &str, metadata: &BookMetadata, write_file: bool) -> Result<BookInfo> {
+    let mut info = BookInfo::get_specific_info(id)?;
+    info.apply_metadata(metadata);
 
-    let mime: mime::Mime = mime.parse().unwrap();
-    let mut path = PathBuf::from(read_config().book.cover.clone());
-    let cover_name = info.id.clone() + "." + mime.subtype().as_str();
-    path.push(cover_name);
+    if info.title.is_empty() {
+        return Err(Error::InvalidArgument("Title cannot be empty".to_string()));
+    }
 
-    println!("{:?}", path);
-    let mut file = File::create(path).unwrap();
-    let _ = file.write_all(&cover);
-}
+    if write_file {
+        rewrite_book(&info, metadata)?;
+    }
+    BookInfo::update_info(&info)?;
 
-fn save_book(info: &BookInfo, path: &str) {
-    let book_name = info.id.clone() + ".epub";
-    let mut book_path = PathBuf::from(read_config().book.dir.clone());
-    book_path.push(book_name);
+    // 正在阅读时同步书本信息
+    if let Some(book) = CURRENT_BOOK.lock().unwrap().as_mut() {
+        if book.info().id == id {
+            book.set_info(info.clone());
+        }
+    }
 
-    let mut src_file = File::open(path).unwrap();
-    let mut dest_file = File::create(book_path).unwrap();
-    let mut buffer = Vec::new();
-    let _ = src_file.read_to_end(&mut buffer);
-    let _ = dest_file.write_all(&buffer);
+    Ok(info)
 }
 
-fn save_resources(info: &BookInfo, book: &mut EpubDoc<BufReader<File>>) {
-    let resources = book.resources.clone();
-    let mut resources_path = PathBuf::from(read_config().book.resources.clone());
-    resources_path = resources_path.join(info.id.as_str());
+// 将元数据写入 epub 文件, 书本正在阅读时先关闭书本, 写入后重新打开
+fn rewrite_book(info: &BookInfo, metadata: &BookMetadata) -> Result<()> {
+    let is_epub = info
+        .file_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"));
+    if !is_epub {
+        return Err(Error::InvalidArgument(
+            "Only EPUB files support writing metadata".to_string(),
+        ));
+    }
 
-    for (_, (path, mime)) in resources.iter() {
-        let mime: mime::Mime = mime.parse().unwrap();
+    let mut container = CURRENT_BOOK.lock().unwrap();
+    let page = match container.as_ref() {
+        Some(book) if book.info().id == info.id => Some(book.current_page()),
+        _ => None,
+    };
+    if page.is_some() {
+        *container = None;
+    }
+
+    let result = write_metadata(&info.file_path, metadata);
 
-        if mime.type_().as_str() != "image" {
-            continue;
+    if let Some(page) = page {
+        match book::open(info) {
+            Ok(mut book) => {
+                book.set_current_page(page);
+                *container = Some(book);
+            }
+            Err(err) => warn!("重新打开书本 {} 失败: {}", info.title, err),
         }
+    }
+
+    let backup = result?;
+    info!(
+        "已将元数据写入 {:?}, 原始文件备份为 {:?}",
+        info.file_path, backup
+    );
+
+    Ok(())
+}
+
+/// 获取书本中的全部图片, 用于选择封面
+///
+/// 参数: id
+///
+/// 返回 Response<Vec<PathBuf>>, 为图片的存储路径
+#[tauri::command]
+pub fn get_book_images(id: &str) -> Response<Vec<PathBuf>> {
+    BookInfo::get_specific_info(id)
+        .and_then(|info| book_images(&info))
+        .into()
+}
+
+/// 替换书本的封面, 同时重新生成缩略图
+///
+/// 参数: id, path 图片的路径, 可以是任意图片文件或 get_book_images 返回的图片
+///
+/// 返回 Response<BookInfo>, 为修改后的书本信息
+#[tauri::command]
+pub fn set_cover(id: &str, path: &str) -> Response<BookInfo> {
+    change_cover(id, Path::new(path)).into()
+}
+
+fn change_cover(id: &str, path: &Path) -> Result<BookInfo> {
+    let mut info = BookInfo::get_specific_info(id)?;
+
+    info.cover_path = replace_cover(&info, &fs::read(path)?)?;
+    info.thumbnails = thumbnail_paths(&info.cover_path);
+    BookInfo::update_info(&info)?;
+
+    Ok(info)
+}
+
+/// 删除书本
+///
+/// 书本的数据与文件会移动到回收站, 保留 trash_days 天后清理;
+/// trash_days 为 0 时直接删除
+///
+/// 参数: id
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn remove_book(id: &str) -> Response<()> {
+    remove(id).into()
+}
+
+fn remove(id: &str) -> Result<()> {
+    let info = BookInfo::get_specific_info(id)?;
+    close_book(id);
 
-        let image = resources_path.join(path);
-        let mut target_path = image.clone();
-        target_path.pop();
+    if read_config().setting.trash_days == 0 {
+        BookInfo::remove_info(id)?;
+        remove_files(&info);
 
-        if !path.exists() {
-            let _ = create_dir_all(target_path);
+        info!("已删除书本 {}", info.title);
+        return Ok(());
+    }
+
+    let item = TrashItem {
+        marks: BookMark::get_mark_list(id)?,
+        progress: ReadingProgress::get_progress(id).ok(),
+        shelves: Shelf::get_book_shelves(id)?
+            .into_iter()
+            .map(|shelf| shelf.id)
+            .collect(),
+        tags: Tag::get_book_tags(id)?,
+        deleted_time: time_stamp(),
+        info,
+    };
+    move_to_trash(&item)?;
+
+    // 删除数据失败时将文件移回原位置
+    if let Err(err) = BookInfo::remove_info(id) {
+        match restore_from_trash(id) {
+            Ok(_) => clear_trash(id),
+            Err(err) => warn!("恢复书本 {} 的文件失败: {}", item.info.title, err),
         }
+        return Err(err);
+    }
 
-        let mut file = File::create(image).unwrap();
+    info!("已将书本 {} 移动到回收站", item.info.title);
+    Ok(())
+}
 
-        let buff = book.get_resource_by_path(path).unwrap();
-        let _ = file.write_all(&buff);
+// 删除的书本正在阅读时关闭书本
+fn close_book(id: &str) {
+    let mut container = CURRENT_BOOK.lock().unwrap();
+
+    if container.as_ref().is_some_and(|book| book.info().id == id) {
+        *container = None;
+        end_session();
     }
-    // image_list
 }
 
-// TODO: 重写函数
+/// 获取回收站中的书本
+///
+/// 返回 Response<TrashItem[]>, 按删除时间倒序排列
 #[tauri::command]
-pub fn search_book(key: &str) {
-    println!("{}", key);
+pub fn get_trash_list() -> Response<Vec<TrashItem>> {
+    trash_list().into()
 }
 
-/// 获取当前打开书本的 css 文件
+/// 从回收站恢复书本, 同时恢复书签, 阅读进度, 书架与标签
 ///
-/// 返回一个 Json Object：
-/// {
-///     "css": string,
-///     "success": boolean,
-/// }
+/// 参数: id
+///
+/// 返回 Response<BookInfo>
 #[tauri::command]
-pub fn get_css() -> String {
-    let result;
+pub fn restore_book(id: &str) -> Response<BookInfo> {
+    restore(id).into()
+}
 
-    match CURRENT_BOOK.lock().unwrap().as_mut() {
-        Some(book) => {
-            let css_list = book.get_css();
+fn restore(id: &str) -> Result<BookInfo> {
+    if BookInfo::get_specific_info(id).is_ok() {
+        return Err(Error::InvalidArgument(
+            "The book is already in the library".to_string(),
+        ));
+    }
+
+    let item = restore_from_trash(id)?;
+
+    // 写入数据失败时将文件重新移回回收站
+    if let Err(err) = BookInfo::insert_info(&item.info) {
+        if let Err(err) = move_to_trash(&item) {
+            warn!("将书本 {} 移回回收站失败: {}", item.info.title, err);
+        }
+        return Err(err);
+    }
 
-            result = json!({
-                "success": true,
-                "css": css_list,
-            });
+    for mark in item.marks {
+        if let Err(err) = BookMark::restore_mark(mark) {
+            warn!("恢复书签失败: {}", err);
         }
-        None => {
-            // TODO: 增加返回的信息
-            result = json!({
-                "success": false,
-            });
+    }
+    if let Some(progress) = &item.progress {
+        if let Err(err) = ReadingProgress::save_progress(progress) {
+            warn!("恢复阅读进度失败: {}", err);
         }
     }
+    if let Err(err) = Tag::set_book_tags(id, &item.tags) {
+        warn!("恢复标签失败: {}", err);
+    }
+    for shelf in &item.shelves {
+        // 书架已被删除时返回 NotFound, 忽略即可
+        let _ = Shelf::add_books(*shelf, &[id.to_string()]);
+    }
+    index_book(&item.info);
+    clear_trash(id);
+
+    info!("已从回收站恢复书本 {}", item.info.title);
+    Ok(item.info)
+}
+
+/// 在书库中搜索内容
+///
+/// 参数: key 关键字
+///
+/// 返回 Response<SearchResult[]>
+#[tauri::command]
+pub fn search_book(key: &str) -> Response<Vec<SearchResult>> {
+    SearchResult::search(key).into()
+}
 
-    json_to_string(&result)
+/// 获取当前打开书本的 css 文件
+///
+/// 返回 Response<Record<string, string>>, key 为文件名, value 为文件内容
+#[tauri::command]
+pub fn get_css() -> Response<HashMap<String, String>> {
+    with_book(|book| Ok(book.get_css())).into()
 }
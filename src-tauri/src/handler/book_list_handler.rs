@@ -1,15 +1,36 @@
-use log::error;
+use crate::entity::{
+    bookinfo::BookInfo,
+    query::{BookPage, BookQuery},
+    response::Response,
+    series::Series,
+};
 
-use crate::entity::bookinfo::BookInfo;
+/// 获取书库中的书本
+///
+/// 参数: query 查询条件, 包括排序方式, 筛选条件与分页
+///
+/// 返回 Response<BookPage>, 为当前页的书本与满足条件的书本总数
+#[tauri::command]
+pub fn book_list(query: BookQuery) -> Response<BookPage> {
+    BookInfo::query_info_list(&query).into()
+}
+
+/// 获取书库中的全部系列
+///
+/// 返回 Response<Series[]>, 按系列名称排序, 系列中的书本按序号排列
+#[tauri::command]
+pub fn get_series_list() -> Response<Vec<Series>> {
+    Series::get_series_list().into()
+}
 
+/// 获取同一系列中的下一本书, 用于读完一本书后推荐
+///
+/// 参数: id 当前书本的唯一标识符
+///
+/// 返回 Response<BookInfo | null>, 书本不属于任何系列或已是最后一本时为 null
 #[tauri::command]
-pub fn book_list() -> String {
-    let list = BookInfo::get_info_list();
-    match list {
-        Ok(list) => serde_json::to_string(&list).unwrap(),
-        Err(err) => {
-            error!("读取数据时出现错误: {}", err);
-            panic!();
-        }
-    }
+pub fn next_in_series(id: &str) -> Response<Option<BookInfo>> {
+    BookInfo::get_specific_info(id)
+        .and_then(|info| BookInfo::get_next_in_series(&info))
+        .into()
 }
@@ -1,107 +1,155 @@
-use log::warn;
-use serde_json::json;
+use std::{collections::HashMap, fs, path::PathBuf};
 
 use crate::{
     entity::{
-        bookmark::BookMark,
-        notification::{Notification, NotificationType},
+        book, bookinfo::BookInfo, bookmark::BookMark, catalog::CatalogItem, response::Response,
+    },
+    error::{Error, Result},
+    utils::{
+        common_utils::time_stamp,
+        export_utils::{extract_text, render, ExportEntry, ExportFormat},
     },
-    utils::common_utils::json_to_string,
 };
 
+use super::with_book;
+
+/// 添加书签
+///
+/// 参数: mark 书签
+///
+/// 返回 Response<null>
 #[tauri::command]
-pub fn add_bookmark(data: &str) -> String {
-    let mut success = false;
-
-    match serde_json::from_str::<BookMark>(data) {
-        Ok(mark) => {
-            let mark = mark.update();
-            if !BookMark::insert_mark(&mark) {
-                warn!("添加书签失败");
-            } else {
-                success = true;
-            }
-        }
-        Err(err) => {
-            warn!("解析书签时发生错误: {}", err);
-            warn!("书签数据: {}", data);
-        }
-    }
+pub fn add_bookmark(mark: BookMark) -> Response<()> {
+    page_count(&mark.book_id)
+        .and_then(|count| BookMark::insert_mark(&mark.update(), count))
+        .into()
+}
 
-    let result;
-    if success {
-        result = json!({
-            "success": true,
-        });
-    } else {
-        let msg = Notification {
-            r#type: NotificationType::Warn,
-            title: "Warn".to_string(),
-            msg: "添加书签失败, 详细信息请查看日志记录".to_string(),
-        };
+/// 更新书签的范围与批注内容
+///
+/// 参数: mark 书签, 通过 mark_id 确定需要更新的书签
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn update_mark(mut mark: BookMark) -> Response<()> {
+    mark.update_time = time_stamp();
 
-        result = json!({
-            "success": false,
-            "msg": msg,
-        });
+    page_count(&mark.book_id)
+        .and_then(|count| BookMark::update_mark(&mark, count))
+        .into()
+}
+
+// 书签所属书本的章节总数, 书本已打开时不再重复读取文件
+fn page_count(id: &str) -> Result<usize> {
+    let current = with_book(|book| Ok((book.info().id == id).then(|| book.page_count())));
+
+    match current {
+        Ok(Some(count)) => Ok(count),
+        _ => Ok(book::open(&BookInfo::get_specific_info(id)?)?.page_count()),
     }
+}
 
-    json_to_string(&result)
+/// 获取书本的全部书签, 按起始位置排序
+///
+/// 参数: id 书本的唯一标识符
+///
+/// 返回 Response<BookMark[]>
+#[tauri::command]
+pub fn get_book_mark_list(id: &str) -> Response<Vec<BookMark>> {
+    BookMark::get_mark_list(id).into()
 }
 
+/// 获取指定章节的书签
+///
+/// 参数: id 书本的唯一标识符, chapter 章节索引
+///
+/// 返回 Response<BookMark[]>
 #[tauri::command]
-pub fn get_chapter_mark_list(id: &str, chapter: usize) -> String {
-    let result;
-
-    match BookMark::get_mark_list_by_chapter(id, chapter) {
-        Ok(list) => {
-            result = json!({
-                "success": true,
-                "list": list,
-            });
-        }
-        Err(err) => {
-            let msg = Notification {
-                r#type: NotificationType::Warn,
-                title: "Warn".to_string(),
-                msg: "获取书签信息时发生了错误".to_string(),
-            };
-
-            result = json!({
-                "success": false,
-                "msg":msg,
-            });
-            warn!("查询数据时发生了错误: {:?}", err);
-        }
-    }
+pub fn get_chapter_mark_list(id: &str, chapter: usize) -> Response<Vec<BookMark>> {
+    BookMark::get_mark_list_by_chapter(id, chapter).into()
+}
 
-    json_to_string(&result)
+/// 删除书签
+///
+/// 参数: id 书签的唯一标识符
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn delete_mark(id: usize) -> Response<()> {
+    BookMark::remove_mark(id).into()
 }
 
+/// 导出书本的全部书签与笔记
+///
+/// 参数: id 书本的唯一标识符, format 导出格式 (markdown, json, html), path 导出文件的路径
+///
+/// 返回 Response<string>, data 为导出文件的路径
 #[tauri::command]
-pub fn delete_mark(id: usize) -> String {
-    let result;
-
-    match BookMark::remove_mark(id) {
-        true => {
-            result = json!({
-                "success": true,
-            });
-        }
-        false => {
-            let msg = Notification {
-                r#type: NotificationType::Warn,
-                title: "Warn".to_string(),
-                msg: "An error occurred while getting info".to_string(),
-            };
-
-            result = json!({
-                "success": false,
-                "msg":msg,
-            });
-            warn!("删除书签数据时发生了错误");
-        }
+pub fn export_marks(id: &str, format: &str, path: &str) -> Response<PathBuf> {
+    export(id, format, path).into()
+}
+
+fn export(id: &str, format: &str, path: &str) -> Result<PathBuf> {
+    let format = match ExportFormat::parse(format) {
+        Some(format) => format,
+        None => return Err(Error::InvalidArgument(format!("export format {}", format))),
+    };
+
+    let info = BookInfo::get_specific_info(id)?;
+    let marks = BookMark::get_mark_list(id)?;
+
+    let mut book = book::open(&info)?;
+    let catalog = book.get_catalog();
+    let page_count = book.page_count();
+
+    // 同一章节的段落只读取一次
+    let mut pages: HashMap<usize, Vec<String>> = HashMap::new();
+    let mut entries = Vec::new();
+    for mark in marks {
+        let chapter = mark.start().chapter;
+        let chapter_title = match CatalogItem::find_by_chapter(&catalog, chapter) {
+            Some(item) => item.label.clone(),
+            None => info.title.clone(),
+        };
+
+        // 起始章节超出书本范围的书签没有可以摘录的内容, 结束章节截断到最后一章
+        let quote = if !mark.text.is_empty() {
+            mark.text.clone()
+        } else if chapter < page_count {
+            let range = chapter..=mark.end().chapter.clamp(chapter, page_count - 1);
+            for index in range.clone() {
+                pages
+                    .entry(index)
+                    .or_insert_with(|| book.get_page_text(index));
+            }
+            let chapters = range
+                .map(|index| pages[&index].as_slice())
+                .collect::<Vec<_>>();
+            extract_text(&chapters, mark.start(), mark.end())
+        } else {
+            String::new()
+        };
+
+        entries.push(ExportEntry {
+            chapter_title,
+            start: mark.start().clone(),
+            end: mark.end().clone(),
+            quote,
+            note: mark.note,
+            tags: mark.tags,
+            color: mark.color,
+            style: mark.style,
+            create_time: mark.create_time,
+            update_time: mark.update_time,
+        });
     }
 
-    json_to_string(&result)
+    let mut path = PathBuf::from(path);
+    if path.extension().is_none() {
+        path.set_extension(format.extension());
+    }
+
+    fs::write(&path, render(format, &info, &entries))?;
+
+    Ok(path)
 }
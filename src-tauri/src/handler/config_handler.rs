@@ -1,26 +1,50 @@
-use crate::utils::config_utils::{Config, read_config, save_config};
+use tauri::AppHandle;
 
-// TODO: 调整接口的返回值
+use crate::{
+    entity::response::Response,
+    error::Result,
+    handler::book_handler::watch_library,
+    utils::config_utils::{load_config, save_config, Config},
+};
 
+/// 获取配置信息
+///
+/// 返回 Response<Config>
 #[tauri::command]
-pub fn get_config() -> String {
-    let config = read_config();
-    let json = serde_json::to_string(&config).unwrap();
-
-    json
+pub fn get_config() -> Response<Config> {
+    load_config().into()
 }
 
+/// 更新配置信息, 配置没有变化时不写入文件
+///
+/// 监视的文件夹变化时重新同步并监视
+///
+/// 参数: config 新的配置信息
+///
+/// 返回 Response<null>
 #[tauri::command]
-pub fn update_config(config: String) {
-    let origin_config = read_config();
-    let config: Config = serde_json::from_str(&config).unwrap();
+pub fn update_config(app: AppHandle, config: Config) -> Response<()> {
+    update(app, &config).into()
+}
+
+fn update(app: AppHandle, config: &Config) -> Result<()> {
+    let origin_config = load_config()?;
 
-    if origin_config != config {
-        save_config(config);
+    if &origin_config != config {
+        save_config(config)?;
     }
+
+    if origin_config.setting.watch_folders != config.setting.watch_folders {
+        watch_library(app, config.setting.watch_folders.clone());
+    }
+
+    Ok(())
 }
 
+/// 获取书本资源的保存路径
+///
+/// 返回 Response<string>
 #[tauri::command]
-pub fn get_resource_path() -> String {
-    read_config().book.resources.clone()
+pub fn get_resource_path() -> Response<String> {
+    load_config().map(|config| config.book.resources).into()
 }
@@ -1,13 +1,29 @@
 use std::sync::{LazyLock, Mutex};
 
-use crate::entity::epub::Epub;
+use crate::{
+    entity::book::Book,
+    error::{Error, Result},
+};
 
 pub mod book_handler;
 pub mod book_list_handler;
 pub mod bookmark_handler;
 pub mod config_handler;
 pub mod read_handler;
+pub mod shelf_handler;
+pub mod stats_handler;
 
-/// 当前打开的Epub
+/// 当前打开的书本
 // static mut CURRENT_BOOK: Option<Epub> = None;
-static CURRENT_BOOK: LazyLock<Mutex<Option<Epub>>> = LazyLock::new(|| Mutex::new(None));
+static CURRENT_BOOK: LazyLock<Mutex<Option<Box<dyn Book>>>> = LazyLock::new(|| Mutex::new(None));
+
+/// 使用当前打开的书本执行操作, 没有打开的书本时返回 NoBookOpen
+fn with_book<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce(&mut dyn Book) -> Result<T>,
+{
+    match CURRENT_BOOK.lock().unwrap().as_mut() {
+        Some(book) => f(book.as_mut()),
+        None => Err(Error::NoBookOpen),
+    }
+}
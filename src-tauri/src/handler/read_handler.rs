@@ -1,194 +1,178 @@
 //! 处理阅读内容请求的一些方法
-use serde_json::json;
+use log::warn;
 
 use crate::{
-    entity::notification::{Notification, NotificationType},
-    utils::common_utils::json_to_string,
+    entity::{
+        book::Book,
+        bookmark::Position,
+        catalog::CatalogItem,
+        progress::ReadingProgress,
+        response::{ChapterContent, Response},
+        search::SearchOption,
+        word_count::WordCount,
+    },
+    error::Error,
+    utils::{
+        config_utils::read_config,
+        session_utils::{record_activity, record_chapter},
+    },
 };
 
-use super::CURRENT_BOOK;
+use super::with_book;
 
 /// 获取书籍目录
 ///
-/// 返回一个Json Object：
-/// {
-///     "catalog": string,
-///     "success": boolean,
-///     "error": string
-/// }
+/// 目录项包含章节字数与按设置中的阅读速度估计的阅读时间
+///
+/// 返回 Response<CatalogItem[]>
 #[tauri::command]
-pub fn get_book_catalog() -> String {
-    let result;
-
-    match CURRENT_BOOK.lock().unwrap().as_ref() {
-        Some(book) => {
-            result = json!({
-                "success": true,
-                "catalog": book.get_catalog(),
-            });
-        }
-        None => {
-            let error = Notification {
-                r#type: NotificationType::Err,
-                title: "Error".to_string(),
-                msg: "Has not open any book".to_string(),
-            };
-
-            result = json!({
-                "success": false,
-                "error": error,
-            });
-        }
-    }
-
-    json_to_string(&result)
+pub fn get_book_catalog() -> Response<Vec<CatalogItem>> {
+    with_book(|book| {
+        let mut catalog = book.get_catalog();
+        let counts = WordCount::get_book_counts(&book.info().id)?;
+        CatalogItem::fill_word_counts(&mut catalog, &counts, &read_config().setting);
+
+        Ok(catalog)
+    })
+    .into()
 }
 
 /// 获取上一章
 ///
-/// 返回一个Json Object:
-/// {
-///     "content": string,
-///     "success": boolean,
-///     "msg": string
-/// }
+/// 返回 Response<string>, data 为章节的 HTML 内容
 #[tauri::command]
-pub fn prev_page() -> String {
-    let result;
-
-    match CURRENT_BOOK.lock().unwrap().as_mut() {
-        Some(book) => {
-            if book.go_prev() {
-                result = json!({
-                    "success": true,
-                    "content": book.get_current_page(),
-                });
-            } else {
-                let msg = Notification {
-                    r#type: NotificationType::Warn,
-                    title: "WARN".to_string(),
-                    msg: "No previous page".to_string(),
-                };
-
-                result = json!({
-                    "success": false,
-                    "msg": msg,
-                });
-            }
-        }
-        None => {
-            let msg = Notification {
-                r#type: NotificationType::Err,
-                title: "Error".to_string(),
-                msg: "Has not open any book".to_string(),
-            };
-
-            result = json!({
-                "success": false,
-                "msg": msg,
-            })
+pub fn prev_page() -> Response<String> {
+    with_book(|book| {
+        if !book.go_prev() {
+            return Err(Error::NotFound("Previous page".to_string()));
         }
-    }
 
-    json_to_string(&result)
+        save_progress(book, 0, 0, 0.0);
+        record_chapter(book);
+
+        Ok(book.get_current_page())
+    })
+    .into()
 }
 
 /// 获取下一章
 ///
-/// 返回一个Json Object:
-/// {
-///     "content": string,
-///     "success": boolean,
-///     "msg": string
-/// }
+/// 返回 Response<string>, data 为章节的 HTML 内容
 #[tauri::command]
-pub fn next_page() -> String {
-    let result;
-
-    match CURRENT_BOOK.lock().unwrap().as_mut() {
-        Some(book) => {
-            if book.go_next() {
-                result = json!({
-                    "success": true,
-                    "content": book.get_current_page(),
-                });
-            } else {
-                let msg = Notification {
-                    r#type: NotificationType::Warn,
-                    title: "WARN".to_string(),
-                    msg: "No next page".to_string(),
-                };
-
-                result = json!({
-                    "success": false,
-                    "msg": msg,
-                });
-            }
+pub fn next_page() -> Response<String> {
+    with_book(|book| {
+        if !book.go_next() {
+            return Err(Error::NotFound("Next page".to_string()));
         }
-        None => {
-            let msg = Notification {
-                r#type: NotificationType::Err,
-                title: "Error".to_string(),
-                msg: "Has not open any book".to_string(),
-            };
-
-            result = json!({
-                "success": false,
-                "msg": msg,
-            });
-        }
-    }
 
-    json_to_string(&result)
+        save_progress(book, 0, 0, 0.0);
+        record_chapter(book);
+
+        Ok(book.get_current_page())
+    })
+    .into()
 }
 
 /// 跳转到指定的章节
 ///
-/// 参数: usize 需要跳转到的章节
+/// 参数:
+/// chapter: 需要跳转到的章节
+/// anchor: 章节中的锚点, 可选
 ///
-/// 返回一个 Json Object：
-/// {
-///     "content": string,
-///     "success": boolean,
-///     "msg": string
-/// }
+/// 返回 Response<ChapterContent>
 #[tauri::command]
-pub fn jump_to_chapter(chapter: usize) -> String {
-    let result;
-
-    match CURRENT_BOOK.lock().unwrap().as_mut() {
-        Some(book) => {
-            if book.set_current_page(chapter) {
-                result = json!({
-                    "success": true,
-                    "content": book.get_current_page(),
-                });
-            } else {
-                let msg = Notification {
-                    r#type: NotificationType::Warn,
-                    title: "WARN".to_string(),
-                    msg: "Page not found".to_string(),
-                };
-
-                result = json!({
-                    "success": false,
-                    "msg": msg,
-                });
-            }
-        }
-        None => {
-            let msg = Notification {
-                r#type: NotificationType::Err,
-                title: "Error".to_string(),
-                msg: "Has not open any book".to_string(),
-            };
-
-            result = json!({
-                "success": false,
-                "msg": msg,
-            });
+pub fn jump_to_chapter(chapter: usize, anchor: Option<String>) -> Response<ChapterContent> {
+    with_book(|book| {
+        if !book.set_current_page(chapter) {
+            return Err(Error::NotFound("Page".to_string()));
         }
-    }
 
-    json_to_string(&result)
+        let paragraph = match &anchor {
+            Some(anchor) => book
+                .get_page_anchors(chapter)
+                .get(anchor)
+                .copied()
+                .unwrap_or(0),
+            None => 0,
+        };
+
+        save_progress(book, paragraph, 0, 0.0);
+        record_chapter(book);
+
+        Ok(ChapterContent {
+            content: book.get_current_page(),
+            anchor,
+            paragraph,
+        })
+    })
+    .into()
+}
+
+/// 在当前打开的书本中搜索
+///
+/// 参数:
+/// key: 关键字
+/// case_insensitive: 是否忽略大小写
+/// whole_word: 是否全词匹配
+/// regex: 是否将关键字视为正则表达式
+///
+/// 返回 Response<Position[]>, 最多返回 1000 个匹配项
+#[tauri::command]
+pub fn search_in_book(
+    key: &str,
+    case_insensitive: bool,
+    whole_word: bool,
+    regex: bool,
+) -> Response<Vec<Position>> {
+    let option = SearchOption {
+        case_insensitive,
+        whole_word,
+        regex,
+    };
+
+    with_book(|book| {
+        let pattern = option.build(key)?;
+
+        Ok(book.search(&pattern))
+    })
+    .into()
+}
+
+/// 根据滚动位置获取当前所在的目录项
+///
+/// 参数: paragraph 当前位置的段落索引
+///
+/// 返回 Response<number | null>, data 为目录项的序号, 当前位置之前没有目录项时为 null
+#[tauri::command]
+pub fn get_current_catalog(paragraph: usize) -> Response<Option<usize>> {
+    with_book(|book| Ok(book.locate_catalog(paragraph))).into()
+}
+
+/// 更新当前书本的阅读进度, 由前端在滚动页面时调用
+///
+/// 参数:
+/// paragraph: 当前位置的段落索引
+/// offset: 当前位置在段落中的偏移量
+/// fraction: 当前章节已阅读的比例, 取值范围为 0 ~ 1
+///
+/// 返回 Response<number>, data 为整本书的阅读进度百分比
+#[tauri::command]
+pub fn update_progress(paragraph: usize, offset: usize, fraction: f64) -> Response<f64> {
+    with_book(|book| {
+        let progress = book.get_progress(paragraph, offset, fraction);
+        ReadingProgress::save_progress(&progress)?;
+        record_activity(&progress.book_id);
+
+        Ok(progress.percentage)
+    })
+    .into()
+}
+
+// 保存当前书本的阅读进度, 失败时只记录日志, 不影响翻页
+fn save_progress(book: &dyn Book, paragraph: usize, offset: usize, fraction: f64) {
+    let progress = book.get_progress(paragraph, offset, fraction);
+
+    if let Err(err) = ReadingProgress::save_progress(&progress) {
+        warn!("保存阅读进度失败: {}, {}", progress.book_id, err);
+    }
 }
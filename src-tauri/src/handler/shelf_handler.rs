@@ -0,0 +1,107 @@
+use crate::entity::{response::Response, shelf::Shelf, tag::Tag};
+
+/// 获取全部书架
+///
+/// 返回 Response<Shelf[]>, 按名称排序
+#[tauri::command]
+pub fn get_shelf_list() -> Response<Vec<Shelf>> {
+    Shelf::get_shelf_list().into()
+}
+
+/// 创建书架
+///
+/// 参数: name 书架名称, 不可与已有的书架重复
+///
+/// 返回 Response<Shelf>
+#[tauri::command]
+pub fn create_shelf(name: &str) -> Response<Shelf> {
+    Shelf::create_shelf(name).into()
+}
+
+/// 重命名书架
+///
+/// 参数: id 书架的 id, name 新的名称
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn rename_shelf(id: usize, name: &str) -> Response<()> {
+    Shelf::rename_shelf(id, name).into()
+}
+
+/// 删除书架, 书架中的书本不会被删除
+///
+/// 参数: id 书架的 id
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn delete_shelf(id: usize) -> Response<()> {
+    Shelf::remove_shelf(id).into()
+}
+
+/// 将书本添加到书架
+///
+/// 参数: id 书架的 id, books 书本 id 的列表
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn add_to_shelf(id: usize, books: Vec<String>) -> Response<()> {
+    Shelf::add_books(id, &books).into()
+}
+
+/// 将书本移出书架
+///
+/// 参数: id 书架的 id, books 书本 id 的列表
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn remove_from_shelf(id: usize, books: Vec<String>) -> Response<()> {
+    Shelf::remove_books(id, &books).into()
+}
+
+/// 获取书本所在的全部书架
+///
+/// 参数: id 书本的唯一标识符
+///
+/// 返回 Response<Shelf[]>
+#[tauri::command]
+pub fn get_book_shelves(id: &str) -> Response<Vec<Shelf>> {
+    Shelf::get_book_shelves(id).into()
+}
+
+/// 获取全部标签
+///
+/// 返回 Response<Tag[]>, 按名称排序
+#[tauri::command]
+pub fn get_tag_list() -> Response<Vec<Tag>> {
+    Tag::get_tag_list().into()
+}
+
+/// 获取书本的全部标签
+///
+/// 参数: id 书本的唯一标识符
+///
+/// 返回 Response<string[]>
+#[tauri::command]
+pub fn get_book_tags(id: &str) -> Response<Vec<String>> {
+    Tag::get_book_tags(id).into()
+}
+
+/// 设置书本的标签, 替换原有的全部标签
+///
+/// 参数: id 书本的唯一标识符, tags 标签列表
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn set_book_tags(id: &str, tags: Vec<String>) -> Response<()> {
+    Tag::set_book_tags(id, &tags).into()
+}
+
+/// 重命名标签, 新的标签已存在时合并两个标签
+///
+/// 参数: name 原有的标签, new_name 新的标签, 为空时删除该标签
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn rename_tag(name: &str, new_name: &str) -> Response<()> {
+    Tag::rename_tag(name, new_name).into()
+}
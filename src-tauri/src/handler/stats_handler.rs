@@ -0,0 +1,58 @@
+//! 阅读记录与统计
+use crate::{
+    entity::{
+        response::Response,
+        stats::{BookStats, Period, ReadingStreak, ReadingTotal},
+    },
+    utils::session_utils::record_activity,
+};
+
+use super::with_book;
+
+/// 前端的阅读心跳, 阅读页面处于活动状态时定时调用, 用于延续当前的阅读会话
+///
+/// 返回 Response<null>
+#[tauri::command]
+pub fn reading_heartbeat() -> Response<()> {
+    with_book(|book| {
+        record_activity(&book.info().id);
+        Ok(())
+    })
+    .into()
+}
+
+/// 获取最近一段时间每天或每周的阅读总量
+///
+/// 参数: period 统计周期, count 周期的数量
+///
+/// 返回 Response<ReadingTotal[]>, 按日期排序, 没有阅读的周期不包含在结果中
+#[tauri::command]
+pub fn get_reading_totals(period: Period, count: u32) -> Response<Vec<ReadingTotal>> {
+    ReadingTotal::get_totals(period, count).into()
+}
+
+/// 获取连续阅读的天数
+///
+/// 返回 Response<ReadingStreak>
+#[tauri::command]
+pub fn get_reading_streak() -> Response<ReadingStreak> {
+    ReadingStreak::get_streak().into()
+}
+
+/// 获取单本书的阅读时长与预计剩余时长
+///
+/// 参数: id 书本的唯一标识符
+///
+/// 返回 Response<BookStats>
+#[tauri::command]
+pub fn get_book_stats(id: &str) -> Response<BookStats> {
+    BookStats::get_book_stats(id).into()
+}
+
+/// 获取全部读过的书本的阅读统计
+///
+/// 返回 Response<BookStats[]>, 按阅读时长倒序排列
+#[tauri::command]
+pub fn get_book_stats_list() -> Response<Vec<BookStats>> {
+    BookStats::get_stats_list().into()
+}
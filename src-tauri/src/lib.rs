@@ -1,5 +1,6 @@
 use std::io::{Error, Write};
 use std::sync::{LazyLock, Mutex};
+use std::thread;
 
 use ctor::ctor;
 use flexi_logger::{
@@ -9,11 +10,17 @@ use flexi_logger::{
 use log::{error, info, LevelFilter, Record};
 use rusqlite::Connection;
 
-use handler::{book_handler, book_list_handler, bookmark_handler, config_handler, read_handler};
+use handler::{
+    book_handler, book_list_handler, bookmark_handler, config_handler, read_handler, shelf_handler,
+    stats_handler,
+};
 use utils::config_utils::read_config;
+use utils::import_utils::index_library;
 use utils::resource_utils::resource_integrity_check;
+use utils::trash_utils::purge_trash;
 
 pub mod entity;
+pub mod error;
 pub mod handler;
 pub mod utils;
 
@@ -83,16 +90,51 @@ fn formatter(w: &mut dyn Write, now: &mut DeferredNow, record: &Record) -> Resul
 
 pub fn run() {
     tauri::Builder::default()
+        .setup(|app| {
+            let setting = read_config().setting;
+            purge_trash(setting.trash_days);
+            book_handler::watch_library(app.handle().clone(), setting.watch_folders);
+            thread::spawn(index_library);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             book_handler::book_detail,
             book_handler::open_book,
             book_handler::update_new_book,
+            book_handler::cancel_import,
+            book_handler::update_book_metadata,
+            book_handler::get_book_images,
+            book_handler::set_cover,
+            book_handler::remove_book,
+            book_handler::get_trash_list,
+            book_handler::restore_book,
             book_handler::search_book,
             book_handler::get_css,
             book_list_handler::book_list,
+            book_list_handler::get_series_list,
+            book_list_handler::next_in_series,
+            shelf_handler::get_shelf_list,
+            shelf_handler::create_shelf,
+            shelf_handler::rename_shelf,
+            shelf_handler::delete_shelf,
+            shelf_handler::add_to_shelf,
+            shelf_handler::remove_from_shelf,
+            shelf_handler::get_book_shelves,
+            shelf_handler::get_tag_list,
+            shelf_handler::get_book_tags,
+            shelf_handler::set_book_tags,
+            shelf_handler::rename_tag,
+            stats_handler::reading_heartbeat,
+            stats_handler::get_reading_totals,
+            stats_handler::get_reading_streak,
+            stats_handler::get_book_stats,
+            stats_handler::get_book_stats_list,
             bookmark_handler::add_bookmark,
+            bookmark_handler::update_mark,
+            bookmark_handler::get_book_mark_list,
             bookmark_handler::get_chapter_mark_list,
             bookmark_handler::delete_mark,
+            bookmark_handler::export_marks,
             config_handler::get_config,
             config_handler::update_config,
             config_handler::get_resource_path,
@@ -100,6 +142,9 @@ pub fn run() {
             read_handler::next_page,
             read_handler::jump_to_chapter,
             read_handler::get_book_catalog,
+            read_handler::get_current_catalog,
+            read_handler::search_in_book,
+            read_handler::update_progress,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -6,42 +6,30 @@ use std::{
 };
 
 use crypto_hash::{Algorithm, Hasher};
-use log::warn;
-use serde::Serialize;
 
-pub fn hash(path: &PathBuf) -> String {
-    let mut file = File::open(path).unwrap();
+use crate::error::Result;
+
+/// 计算文件内容的 SHA256, 作为书本的 id
+pub fn hash(path: &PathBuf) -> Result<String> {
+    let mut file = File::open(path)?;
 
     let mut hasher = Hasher::new(Algorithm::SHA256);
     let mut buffer = [0; 2048];
     loop {
-        let bytes = file.read(&mut buffer).unwrap();
+        let bytes = file.read(&mut buffer)?;
         if bytes == 0 {
             break;
         }
 
-        let _ = hasher.write(&mut buffer);
+        hasher.write_all(&buffer[..bytes])?;
     }
 
-    hasher
+    Ok(hasher
         .finish()
         .iter()
         .map(|byte| format!("{:02x}", byte))
         .collect::<Vec<String>>()
-        .join("")
-}
-
-pub fn json_to_string<T>(data: &T) -> String
-where
-    T: Serialize,
-{
-    match serde_json::to_string(&data) {
-        Ok(result) => result,
-        Err(error) => {
-            warn!("解析 JSON 数据异常: {}", error);
-            "".to_string()
-        }
-    }
+        .join(""))
 }
 
 /// 返回当前时间戳(ms)
@@ -51,3 +39,30 @@ pub fn time_stamp() -> u64 {
         Err(_) => 0,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use super::hash;
+
+    #[test]
+    fn hash_test() {
+        let path = env::temp_dir().join("reader_hash_test.txt");
+
+        fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            hash(&path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        // 最后一次读取不足一个缓冲区时, 只计算实际读取的内容
+        fs::write(&path, "a".repeat(5000)).unwrap();
+        assert_eq!(
+            hash(&path).unwrap(),
+            "c526c6222044dab5674de9c4ac7f4566ebb5e4d8bf9d8ea34c9cc8a7cc3c869c"
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -6,8 +6,12 @@ use std::{
 };
 
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+use crate::error::Result;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, TS)]
+#[ts(export)]
 pub struct Config {
     pub database: String,
     pub log: String,
@@ -16,7 +20,8 @@ pub struct Config {
     pub setting: Setting,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, TS)]
+#[ts(export)]
 pub struct BookData {
     pub info: String,
     pub dir: String,
@@ -24,12 +29,14 @@ pub struct BookData {
     pub resources: String,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, TS)]
+#[ts(export)]
 pub struct Theme {
     pub appearance: Appearance,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq, TS)]
+#[ts(export)]
 pub enum Appearance {
     #[default]
     Light,
@@ -37,14 +44,42 @@ pub enum Appearance {
     System,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, TS)]
+#[serde(default)]
+#[ts(export)]
 pub struct Setting {
     pub sidebar: bool,
+
+    /// 导入 txt 文件时用于识别章节标题的正则表达式
+    pub chapter_patterns: Vec<String>,
+
+    /// 监视的文件夹, 文件夹中新增的书本会自动导入
+    pub watch_folders: Vec<String>,
+
+    /// 删除的书本在回收站中保留的天数, 为 0 时直接删除
+    pub trash_days: u32,
+
+    /// 阅读速度, 每分钟阅读的单词数, 用于估计章节的阅读时间
+    pub reading_speed: u32,
+
+    /// 中文与日文的阅读速度, 每分钟阅读的字数
+    pub cjk_reading_speed: u32,
 }
 
 impl Default for Setting {
     fn default() -> Self {
-        Self { sidebar: true }
+        Self {
+            sidebar: true,
+            chapter_patterns: vec![
+                r"^第[0-9０-９零〇一二三四五六七八九十百千万两]+[章节回卷集部篇]".to_string(),
+                r"^(?i:chapter)\s*[0-9IVXLC]+\b".to_string(),
+                r"^(序章|序言|楔子|引子|尾声|后记|番外)".to_string(),
+            ],
+            watch_folders: Vec::new(),
+            trash_days: 30,
+            reading_speed: 250,
+            cjk_reading_speed: 400,
+        }
     }
 }
 
@@ -58,19 +93,27 @@ static CONFIG_PATH: LazyLock<String> = LazyLock::new(|| {
     }
 });
 
+/// 读取配置文件
+///
+/// 配置文件在启动时已经过完整性检查, 读取失败时直接退出程序,
+/// 需要处理错误的场景请使用 load_config()
 pub fn read_config() -> Config {
-    let mut file = File::open(CONFIG_PATH.as_str()).expect("Failed to open config file.");
+    load_config().expect("Failed to load config file.")
+}
+
+pub fn load_config() -> Result<Config> {
+    let mut file = File::open(CONFIG_PATH.as_str())?;
     let mut content = String::new();
-    file.read_to_string(&mut content)
-        .expect("Failed to read file");
+    file.read_to_string(&mut content)?;
 
-    serde_yml::from_str(&content).expect("Failed to parse YAML")
+    Ok(serde_yml::from_str(&content)?)
 }
 
-pub fn save_config(config: Config) {
-    let content = serde_yml::to_string(&config).unwrap();
+pub fn save_config(config: &Config) -> Result<()> {
+    let content = serde_yml::to_string(config)?;
+
+    let mut file = File::create(CONFIG_PATH.as_str())?;
+    file.write_all(content.as_bytes())?;
 
-    let mut file = File::create(CONFIG_PATH.as_str()).expect("Failed to create config file.");
-    file.write_all(content.as_bytes())
-        .expect("Failed to write to file");
+    Ok(())
 }
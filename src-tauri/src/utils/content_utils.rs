@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use encoding_rs::{Encoding, GB18030, UTF_8};
+use scraper::{node::Element, ElementRef, Html, Node, Selector};
+
+use crate::entity::word_count::WordCount;
+
+/// 提取页面中的段落文本
+///
+/// 段落的划分规则与前端 contentParser 保持一致:
+/// body 下每个元素的子节点中, 文本内容不为空的节点视为一个段落,
+/// 段落文本为该节点下所有文本节点去除首尾空白后拼接的结果.
+/// 因此返回值的下标即为前端渲染后的段落 id.
+pub fn paragraphs(html: &str) -> Vec<String> {
+    walk(html).0
+}
+
+/// 获取页面中锚点(元素 id)所在的段落索引
+///
+/// 前端渲染时会丢弃原有的元素 id, 因此需要将锚点转换为段落索引后再定位.
+/// 位于空节点上的锚点(如 `<a id="sec"/>`)指向其后的第一个段落.
+pub fn anchors(html: &str) -> HashMap<String, usize> {
+    walk(html).1
+}
+
+// 按段落规则遍历页面, 同时记录锚点所在的段落
+fn walk(html: &str) -> (Vec<String>, HashMap<String, usize>) {
+    let document = Html::parse_document(html);
+    let body_selector = Selector::parse("body").unwrap();
+
+    let mut list = Vec::new();
+    let mut anchors = HashMap::new();
+
+    let body = match document.select(&body_selector).next() {
+        Some(body) => body,
+        None => return (list, anchors),
+    };
+
+    // 尚未确定所在段落的锚点
+    let mut pending = Vec::new();
+    for block in body.children().filter_map(ElementRef::wrap) {
+        pending.extend(element_id(block.value()));
+
+        for node in block.children() {
+            let (text, ids) = match node.value() {
+                Node::Text(text) => (text.trim().to_string(), Vec::new()),
+                Node::Element(_) => {
+                    let element = ElementRef::wrap(node).unwrap();
+                    let text = element
+                        .text()
+                        .map(|text| text.trim())
+                        .collect::<String>();
+                    let ids = element
+                        .descendants()
+                        .filter_map(|node| node.value().as_element())
+                        .flat_map(element_id)
+                        .collect::<Vec<String>>();
+
+                    (text, ids)
+                }
+                _ => continue,
+            };
+
+            if text.is_empty() {
+                pending.extend(ids);
+                continue;
+            }
+
+            for id in pending.drain(..).chain(ids) {
+                anchors.entry(id).or_insert(list.len());
+            }
+            list.push(text);
+        }
+    }
+
+    // 页面末尾的锚点指向最后一个段落
+    let last = list.len().saturating_sub(1);
+    for id in pending {
+        anchors.entry(id).or_insert(last);
+    }
+
+    (list, anchors)
+}
+
+fn element_id(element: &Element) -> Option<String> {
+    element.id().map(|id| id.to_string())
+}
+
+/// 将文本文件的内容解码为字符串
+///
+/// 优先根据 BOM 判断编码, 没有 BOM 时若内容为合法的 UTF-8 则按 UTF-8 解码,
+/// 否则按 GB18030 解码(兼容 GBK 与 GB2312)
+pub fn decode_text(bytes: &[u8]) -> String {
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => GB18030,
+    };
+
+    // decode() 会自动跳过 BOM
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// 统计段落的字数
+///
+/// 中文与日文的每个字符计为一个字, 其他文字中连续的字母与数字计为一个单词,
+/// 标点与空白不计入
+pub fn count_words(paragraphs: &[String]) -> WordCount {
+    let mut count = WordCount::default();
+
+    for paragraph in paragraphs {
+        let mut in_word = false;
+        for c in paragraph.chars() {
+            if is_cjk(c) {
+                count.characters += 1;
+                in_word = false;
+            } else if c.is_alphanumeric() || (in_word && (c == '\'' || c == '’')) {
+                if !in_word {
+                    count.words += 1;
+                }
+                in_word = true;
+            } else {
+                in_word = false;
+            }
+        }
+    }
+
+    count
+}
+
+// 汉字, 平假名与片假名
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{ff66}'..='\u{ff9f}'
+        | '\u{20000}'..='\u{2fa1f}')
+}
+
+/// 转义 HTML 中的特殊字符
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::word_count::WordCount;
+
+    use super::{anchors, count_words, decode_text, paragraphs};
+
+    #[test]
+    fn paragraphs_test() {
+        let html = r#"<html><body>
+            <div>
+                <h1>Chapter <span>1</span></h1>
+                <p>  first  </p>
+                <p> </p>
+                second
+            </div>
+            <section><p>third</p></section>
+        </body></html>"#;
+
+        assert_eq!(
+            paragraphs(html),
+            vec!["Chapter1", "first", "second", "third"]
+        );
+    }
+
+    #[test]
+    fn anchors_test() {
+        let html = r#"<html><body>
+            <div id="top">
+                <h1 id="title">Title</h1>
+                <a id="sec1"></a>
+                <p>first <span id="inner">inner</span></p>
+            </div>
+            <div><p id="sec2">second</p><a id="end"/></div>
+        </body></html>"#;
+
+        let anchors = anchors(html);
+        assert_eq!(anchors["top"], 0);
+        assert_eq!(anchors["title"], 0);
+        assert_eq!(anchors["sec1"], 1);
+        assert_eq!(anchors["inner"], 1);
+        assert_eq!(anchors["sec2"], 2);
+        assert_eq!(anchors["end"], 2);
+    }
+
+    #[test]
+    fn decode_test() {
+        // "第一章" 的 GBK 编码
+        let gbk = [0xb5, 0xda, 0xd2, 0xbb, 0xd5, 0xc2];
+        assert_eq!(decode_text(&gbk), "第一章");
+
+        let utf8 = "\u{feff}第一章".as_bytes();
+        assert_eq!(decode_text(utf8), "第一章");
+    }
+
+    #[test]
+    fn count_words_test() {
+        let paragraphs = vec![
+            "Don't panic, it's 42!".to_string(),
+            "第一章 「こんにちは」".to_string(),
+            "Rust 语言".to_string(),
+        ];
+
+        assert_eq!(
+            count_words(&paragraphs),
+            WordCount {
+                words: 5,
+                characters: 10
+            }
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{imageops::FilterType, DynamicImage, ImageFormat, ImageReader};
+use log::warn;
+
+use crate::{
+    entity::bookinfo::BookInfo,
+    error::Result,
+    utils::{config_utils::read_config, content_utils::escape_html, import_utils::resources_dir},
+};
+
+/// 缩略图的宽度, 分别用于普通屏幕与高分辨率屏幕, 高度按封面比例缩放
+pub const THUMBNAIL_WIDTHS: [u32; 2] = [160, 320];
+
+/// 占位封面的尺寸, 与常见封面的比例(3:4)一致
+const PLACEHOLDER_WIDTH: usize = 300;
+const PLACEHOLDER_HEIGHT: usize = 400;
+
+/// 占位封面标题每行的最大字符数与最大行数
+const TITLE_LINE_LEN: usize = 10;
+const TITLE_MAX_LINES: usize = 4;
+
+/// 占位封面的背景颜色, 根据书本 id 选择, 同一本书的颜色保持不变
+const PLACEHOLDER_COLORS: [&str; 6] = [
+    "#396cd8", "#3c8d6e", "#b5563c", "#7a4fa3", "#a6813a", "#4a5a6a",
+];
+
+/// 为没有封面的书本生成 SVG 格式的占位封面, 内容为书名与作者
+pub fn placeholder_cover(id: &str, title: &str, creator: &str) -> String {
+    let seed = id.bytes().fold(0usize, |acc, byte| acc + byte as usize);
+    let color = PLACEHOLDER_COLORS[seed % PLACEHOLDER_COLORS.len()];
+
+    let chars = title.trim().chars().collect::<Vec<char>>();
+    let mut lines = chars
+        .chunks(TITLE_LINE_LEN)
+        .map(|line| line.iter().collect::<String>())
+        .collect::<Vec<String>>();
+    if lines.len() > TITLE_MAX_LINES {
+        lines.truncate(TITLE_MAX_LINES);
+        lines[TITLE_MAX_LINES - 1].push('…');
+    }
+
+    let title = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            format!(
+                r#"<tspan x="50%" dy="{}">{}</tspan>"#,
+                if index == 0 { "0" } else { "1.3em" },
+                escape_html(line)
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">
+<rect width="100%" height="100%" fill="{color}"/>
+<rect x="16" y="16" width="{inner_width}" height="{inner_height}" fill="none" stroke="#ffffff" stroke-opacity="0.5"/>
+<text y="120" fill="#ffffff" font-size="26" font-family="sans-serif" text-anchor="middle">{title}</text>
+<text x="50%" y="340" fill="#ffffff" fill-opacity="0.8" font-size="16" font-family="sans-serif" text-anchor="middle">{creator}</text>
+</svg>
+"##,
+        width = PLACEHOLDER_WIDTH,
+        height = PLACEHOLDER_HEIGHT,
+        inner_width = PLACEHOLDER_WIDTH - 32,
+        inner_height = PLACEHOLDER_HEIGHT - 32,
+        color = color,
+        title = title,
+        creator = escape_html(creator),
+    )
+}
+
+/// 生成并保存占位封面, 返回封面的存储路径
+pub fn save_placeholder(info: &BookInfo) -> Result<PathBuf> {
+    let mut path = PathBuf::from(read_config().book.cover.clone());
+    path.push(info.id.clone() + ".svg");
+
+    fs::write(
+        &path,
+        placeholder_cover(&info.id, &info.title, &info.creator),
+    )?;
+
+    Ok(path)
+}
+
+/// 根据封面的 MIME 类型确定封面的扩展名, 无法识别时使用 jpg
+pub fn cover_extension(mime: &str) -> &'static str {
+    match ImageFormat::from_mime_type(mime) {
+        Some(format) => format.extensions_str()[0],
+        None if mime == "image/svg+xml" => "svg",
+        None => "jpg",
+    }
+}
+
+/// 封面的缩略图路径, 与封面保存在同一目录, 文件名为 `<封面文件名>_<宽度>.jpg`
+///
+/// SVG 格式的封面本身足够小, 不生成缩略图
+pub fn thumbnail_paths(cover: &Path) -> Vec<PathBuf> {
+    let stem = match cover.file_stem() {
+        Some(stem) if cover.extension().is_some_and(|ext| ext != "svg") => stem.to_string_lossy(),
+        _ => return Vec::new(),
+    };
+
+    THUMBNAIL_WIDTHS
+        .iter()
+        .map(|width| cover.with_file_name(format!("{}_{}.jpg", stem, width)))
+        .collect()
+}
+
+/// 根据封面生成缩略图, 已存在的缩略图会被覆盖
+pub fn save_thumbnails(cover: &Path) -> Result<()> {
+    if thumbnail_paths(cover).is_empty() {
+        return Ok(());
+    }
+
+    // 早期版本的封面扩展名不一定准确, 根据内容判断图片格式
+    let image = ImageReader::open(cover)?.with_guessed_format()?.decode()?;
+    write_thumbnails(&image, cover)
+}
+
+fn write_thumbnails(image: &DynamicImage, cover: &Path) -> Result<()> {
+    for (width, path) in THUMBNAIL_WIDTHS.iter().zip(thumbnail_paths(cover)) {
+        // 封面小于缩略图时不放大
+        let thumbnail = match image.width() > *width {
+            true => image.resize(*width, u32::MAX, FilterType::Triangle),
+            false => image.clone(),
+        };
+
+        thumbnail
+            .to_rgb8()
+            .save_with_format(path, ImageFormat::Jpeg)?;
+    }
+
+    Ok(())
+}
+
+/// 使用图片替换书本的封面, 并重新生成缩略图, 返回新的封面路径
+///
+/// 图片无法解析时不修改原有封面
+pub fn replace_cover(info: &BookInfo, data: &[u8]) -> Result<PathBuf> {
+    let format = image::guess_format(data)?;
+    let image = image::load_from_memory_with_format(data, format)?;
+
+    let mut path = PathBuf::from(read_config().book.cover.clone());
+    path.push(info.id.clone() + "." + format.extensions_str()[0]);
+
+    fs::write(&path, data)?;
+    if info.cover_path != path {
+        remove_cover(&info.cover_path);
+    }
+    write_thumbnails(&image, &path)?;
+
+    Ok(path)
+}
+
+/// 书本中的全部图片, 为导入时保存在资源文件夹中的图片, 按路径排序
+pub fn book_images(info: &BookInfo) -> Result<Vec<PathBuf>> {
+    let mut images = Vec::new();
+    let mut dirs = vec![resources_dir(info)];
+
+    while let Some(dir) = dirs.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            match path.is_dir() {
+                true => dirs.push(path),
+                false => images.push(path),
+            }
+        }
+    }
+    images.sort();
+
+    Ok(images)
+}
+
+/// 删除封面与缩略图, 删除失败时只记录日志
+pub fn remove_cover(cover: &Path) {
+    if cover.as_os_str().is_empty() {
+        return;
+    }
+
+    for path in cover_files(cover) {
+        if !path.exists() {
+            continue;
+        }
+
+        if let Err(err) = fs::remove_file(&path) {
+            warn!("删除封面 {:?} 失败: {}", path, err);
+        }
+    }
+}
+
+/// 封面与缩略图的全部文件
+pub fn cover_files(cover: &Path) -> Vec<PathBuf> {
+    let mut files = vec![cover.to_path_buf()];
+    files.extend(thumbnail_paths(cover));
+
+    files
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{cover_extension, placeholder_cover, thumbnail_paths};
+
+    #[test]
+    fn placeholder_test() {
+        let svg = placeholder_cover("id", "<Rust> & 读书笔记", "Ferris");
+        assert!(svg.contains("&lt;Rust&gt; &amp;"));
+        assert!(svg.contains("Ferris"));
+
+        // 过长的标题会被截断
+        let svg = placeholder_cover("id", &"长".repeat(100), "");
+        assert_eq!(svg.matches("<tspan").count(), 4);
+        assert!(svg.contains('…'));
+    }
+
+    #[test]
+    fn thumbnail_test() {
+        assert_eq!(cover_extension("image/jpeg"), "jpg");
+        assert_eq!(cover_extension("image/png"), "png");
+        assert_eq!(cover_extension("image/svg+xml"), "svg");
+
+        assert_eq!(
+            thumbnail_paths(Path::new("/cover/id.png")),
+            vec![
+                PathBuf::from("/cover/id_160.jpg"),
+                PathBuf::from("/cover/id_320.jpg")
+            ]
+        );
+        assert!(thumbnail_paths(Path::new("/cover/id.svg")).is_empty());
+        assert!(thumbnail_paths(Path::new("")).is_empty());
+    }
+}
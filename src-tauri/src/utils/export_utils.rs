@@ -0,0 +1,282 @@
+use serde::Serialize;
+
+use crate::{
+    entity::{
+        bookinfo::BookInfo,
+        bookmark::{MarkStyle, Position},
+    },
+    utils::content_utils::escape_html,
+};
+
+/// 书签导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "markdown" | "md" => Some(ExportFormat::Markdown),
+            "json" => Some(ExportFormat::Json),
+            "html" => Some(ExportFormat::Html),
+            _ => None,
+        }
+    }
+
+    /// 导出文件的扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// 导出的单条书签
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportEntry {
+    /// 书签所在的章节名
+    pub chapter_title: String,
+
+    /// 书签的起始位置
+    pub start: Position,
+
+    /// 书签的结束位置
+    pub end: Position,
+
+    /// 书签范围内的文本
+    pub quote: String,
+
+    pub note: String,
+    pub tags: Vec<String>,
+    pub color: String,
+    pub style: MarkStyle,
+    pub create_time: u64,
+    pub update_time: u64,
+}
+
+#[derive(Serialize)]
+struct ExportBook<'a> {
+    id: &'a str,
+    title: &'a str,
+    creator: &'a str,
+    annotations: &'a [ExportEntry],
+}
+
+/// 按指定格式渲染书签, entries 需要按位置排序
+pub fn render(format: ExportFormat, info: &BookInfo, entries: &[ExportEntry]) -> String {
+    match format {
+        ExportFormat::Markdown => render_markdown(info, entries),
+        ExportFormat::Json => render_json(info, entries),
+        ExportFormat::Html => render_html(info, entries),
+    }
+}
+
+/// 截取指定范围的文本, 偏移量为 UTF-16 偏移量
+///
+/// chapters 为起始章节到结束章节的段落列表, 跨段落的文本使用换行符连接
+pub fn extract_text(chapters: &[&[String]], start: &Position, end: &Position) -> String {
+    let mut list = Vec::new();
+    let last = end.chapter.saturating_sub(start.chapter);
+
+    for (number, paragraphs) in chapters.iter().enumerate().take(last + 1) {
+        let first_paragraph = if number == 0 { start.paragraph } else { 0 };
+        let last_paragraph = if number == last {
+            end.paragraph
+        } else {
+            paragraphs.len().saturating_sub(1)
+        };
+
+        for index in first_paragraph..=last_paragraph {
+            let content = match paragraphs.get(index) {
+                Some(content) => content.encode_utf16().collect::<Vec<u16>>(),
+                None => break,
+            };
+
+            let from = if number == 0 && index == start.paragraph {
+                start.offset.min(content.len())
+            } else {
+                0
+            };
+            let to = if number == last && index == end.paragraph {
+                end.offset.clamp(from, content.len())
+            } else {
+                content.len()
+            };
+
+            list.push(String::from_utf16_lossy(&content[from..to]));
+        }
+    }
+
+    list.join("\n")
+}
+
+fn render_markdown(info: &BookInfo, entries: &[ExportEntry]) -> String {
+    let mut content = format!("# {}\n\n", info.title);
+    if !info.creator.is_empty() {
+        content.push_str(&format!("> {}\n\n", info.creator));
+    }
+
+    let mut current = None;
+    for entry in entries {
+        if current != Some(&entry.chapter_title) {
+            content.push_str(&format!("## {}\n\n", entry.chapter_title));
+            current = Some(&entry.chapter_title);
+        }
+
+        for line in entry.quote.lines() {
+            content.push_str(&format!("> {}\n", line));
+        }
+        content.push('\n');
+
+        if !entry.note.is_empty() {
+            content.push_str(&format!("{}\n\n", entry.note));
+        }
+
+        if !entry.tags.is_empty() {
+            let tags = entry
+                .tags
+                .iter()
+                .map(|tag| format!("#{}", tag.replace(' ', "_")))
+                .collect::<Vec<String>>();
+            content.push_str(&format!("{}\n\n", tags.join(" ")));
+        }
+
+        content.push_str("---\n\n");
+    }
+
+    content
+}
+
+fn render_json(info: &BookInfo, entries: &[ExportEntry]) -> String {
+    let book = ExportBook {
+        id: &info.id,
+        title: &info.title,
+        creator: &info.creator,
+        annotations: entries,
+    };
+
+    serde_json::to_string_pretty(&book).unwrap_or_default()
+}
+
+fn render_html(info: &BookInfo, entries: &[ExportEntry]) -> String {
+    let mut body = format!("<h1>{}</h1>\n", escape_html(&info.title));
+    if !info.creator.is_empty() {
+        body.push_str(&format!(
+            "<p class=\"creator\">{}</p>\n",
+            escape_html(&info.creator)
+        ));
+    }
+
+    let mut current = None;
+    for entry in entries {
+        if current != Some(&entry.chapter_title) {
+            body.push_str(&format!("<h2>{}</h2>\n", escape_html(&entry.chapter_title)));
+            current = Some(&entry.chapter_title);
+        }
+
+        let color = if entry.color.is_empty() {
+            "#396cd8"
+        } else {
+            entry.color.as_str()
+        };
+        body.push_str(&format!(
+            "<div class=\"annotation\">\n<blockquote style=\"border-color: {}\">{}</blockquote>\n",
+            escape_html(color),
+            escape_html(&entry.quote).replace('\n', "<br>")
+        ));
+
+        if !entry.note.is_empty() {
+            body.push_str(&format!(
+                "<p class=\"note\">{}</p>\n",
+                escape_html(&entry.note).replace('\n', "<br>")
+            ));
+        }
+
+        if !entry.tags.is_empty() {
+            let tags = entry
+                .tags
+                .iter()
+                .map(|tag| format!("<span class=\"tag\">{}</span>", escape_html(tag)))
+                .collect::<String>();
+            body.push_str(&format!("<p class=\"tags\">{}</p>\n", tags));
+        }
+
+        body.push_str("</div>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{}</title>
+<style>
+body {{ max-width: 800px; margin: 0 auto; padding: 24px; font-family: sans-serif; line-height: 1.6; }}
+blockquote {{ margin: 0; padding: 4px 12px; border-left: 4px solid; }}
+.creator {{ color: #666; }}
+.annotation {{ margin-bottom: 24px; }}
+.note {{ margin: 8px 0 0 16px; }}
+.tag {{ margin-right: 8px; padding: 0 6px; border-radius: 4px; background-color: #eee; }}
+</style>
+</head>
+<body>
+{}</body>
+</html>
+"#,
+        escape_html(&info.title),
+        body
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::bookmark::Position;
+
+    use super::extract_text;
+
+    #[test]
+    fn extract_test() {
+        let paragraphs = vec!["你好, 世界".to_string(), "second line".to_string()];
+        let chapters = [paragraphs.as_slice()];
+        let position = |paragraph, offset| Position {
+            chapter: 0,
+            paragraph,
+            offset,
+        };
+
+        assert_eq!(
+            extract_text(&chapters, &position(0, 4), &position(0, 6)),
+            "世界"
+        );
+        assert_eq!(
+            extract_text(&chapters, &position(0, 4), &position(1, 6)),
+            "世界\nsecond"
+        );
+        assert_eq!(
+            extract_text(&chapters, &position(1, 7), &position(3, 0)),
+            "line"
+        );
+    }
+
+    #[test]
+    fn extract_chapters_test() {
+        let first = vec!["first one".to_string(), "first two".to_string()];
+        let second = vec!["second one".to_string(), "second two".to_string()];
+        let chapters = [first.as_slice(), second.as_slice()];
+        let position = |chapter, paragraph, offset| Position {
+            chapter,
+            paragraph,
+            offset,
+        };
+
+        assert_eq!(
+            extract_text(&chapters, &position(3, 1, 6), &position(4, 0, 6)),
+            "two\nsecond"
+        );
+    }
+}
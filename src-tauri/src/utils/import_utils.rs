@@ -0,0 +1,627 @@
+//! 导入新书的流程
+//!
+//! 每个文件独立导入, 单个文件失败不会影响其他文件, 导入结果通过 ImportResult 返回
+use std::{
+    collections::HashMap,
+    fs::{self, create_dir_all, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, Mutex,
+    },
+    time::SystemTime,
+};
+
+use epub::doc::EpubDoc;
+use log::warn;
+
+use crate::{
+    entity::{
+        book,
+        bookinfo::BookInfo,
+        import::{ImportProgress, ImportStage, ImportSummary},
+        notification::{Notification, NotificationType},
+        search::SearchResult,
+        word_count::WordCount,
+    },
+    error::{Error, Result},
+    utils::{
+        common_utils::time_stamp,
+        config_utils::read_config,
+        content_utils::{count_words, decode_text},
+        cover_utils::{remove_cover, save_placeholder, save_thumbnails, thumbnail_paths},
+    },
+};
+
+/// 检查重复与保存书本时持有, 防止同一本书被同时导入
+static IMPORT_LOCK: Mutex<()> = Mutex::new(());
+
+/// 导入失败的文件与失败时的文件状态, 文件修改后才会再次自动导入
+static REJECTED: LazyLock<Mutex<HashMap<PathBuf, FileStamp>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 文件的大小与修改时间
+type FileStamp = (u64, SystemTime);
+
+/// 单个文件的导入状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStatus {
+    /// 导入成功
+    Imported,
+
+    /// 书库中已存在相同的书本
+    Duplicate,
+
+    /// 不支持的文件格式
+    Unsupported,
+
+    /// 文件无法读取或解析
+    Corrupt,
+
+    /// 导入成功, 但书本没有封面, 已生成占位封面
+    MissingCover,
+}
+
+/// 单个文件的导入结果
+#[derive(Debug, Clone)]
+pub struct ImportResult {
+    /// 导入的文件路径
+    pub path: PathBuf,
+
+    /// 书本标题, 无法解析时为文件名
+    pub title: String,
+
+    pub status: ImportStatus,
+
+    /// 导入失败的原因
+    pub reason: String,
+}
+
+impl ImportResult {
+    fn new(path: &Path) -> Self {
+        let title = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => path.to_string_lossy().to_string(),
+        };
+
+        ImportResult {
+            path: path.to_path_buf(),
+            title,
+            status: ImportStatus::Imported,
+            reason: String::new(),
+        }
+    }
+
+    fn fail(mut self, status: ImportStatus, reason: String) -> Self {
+        self.status = status;
+        self.reason = reason;
+        self
+    }
+
+    /// 转换为展示给用户的提示信息
+    pub fn notification(&self) -> Notification {
+        let (r#type, msg) = match self.status {
+            ImportStatus::Imported => (NotificationType::Info, format!("Imported {}.", self.title)),
+            ImportStatus::Duplicate => (
+                NotificationType::Warn,
+                format!("{} has been added.", self.title),
+            ),
+            ImportStatus::Unsupported => (
+                NotificationType::Warn,
+                format!("Unsupported format: {}", self.title),
+            ),
+            ImportStatus::Corrupt => (
+                NotificationType::Err,
+                format!("Failed to import {}: {}", self.title, self.reason),
+            ),
+            ImportStatus::MissingCover => (
+                NotificationType::Warn,
+                format!(
+                    "{} has no cover, a placeholder cover was generated.",
+                    self.title
+                ),
+            ),
+        };
+
+        let title = match r#type {
+            NotificationType::Err => "Error",
+            NotificationType::Warn => "Warn",
+            NotificationType::Info => "Info",
+        };
+
+        Notification {
+            r#type,
+            title: title.to_string(),
+            msg,
+        }
+    }
+}
+
+/// 支持导入的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Epub,
+    Txt,
+}
+
+impl Format {
+    fn detect(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("epub") => Some(Format::Epub),
+            Some(ext) if ext.eq_ignore_ascii_case("txt") => Some(Format::Txt),
+            _ => None,
+        }
+    }
+}
+
+/// 展开需要导入的路径
+///
+/// 文件夹会被递归展开为其中支持导入的文件, 隐藏文件与隐藏文件夹会被跳过;
+/// 直接指定的文件保持不变, 不支持的格式在导入时报告
+pub fn expand_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_books(path, &mut files);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    files
+}
+
+fn collect_books(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn!("读取文件夹 {:?} 失败: {}", dir, err);
+            return;
+        }
+    };
+
+    let mut paths = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| !is_hidden(path))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_books(&path, files);
+        } else if is_supported(&path) {
+            files.push(path);
+        }
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().starts_with('.'),
+        None => false,
+    }
+}
+
+/// 是否为支持导入的格式
+pub fn is_supported(path: &Path) -> bool {
+    Format::detect(path).is_some()
+}
+
+/// 依次导入多个文件
+///
+/// 每个文件开始导入前检查 cancel, 取消时已导入的书本会保留,
+/// 每个阶段开始时通过 on_progress 报告进度
+pub fn import_books(
+    paths: &[PathBuf],
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(ImportProgress),
+) -> ImportSummary {
+    let total = paths.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (index, path) in paths.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return ImportSummary {
+                results,
+                cancelled: true,
+            };
+        }
+
+        let file = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => path.to_string_lossy().to_string(),
+        };
+
+        let result = import_book(path, |stage| {
+            on_progress(ImportProgress {
+                file: file.clone(),
+                stage,
+                index,
+                total,
+                percent: (index as f64 + stage.weight()) / total as f64 * 100.0,
+            })
+        });
+
+        record_result(&result);
+        results.push(result.notification());
+    }
+
+    ImportSummary {
+        results,
+        cancelled: false,
+    }
+}
+
+/// 文件是否导入失败过且之后没有修改
+pub fn is_rejected(path: &Path) -> bool {
+    match REJECTED.lock().unwrap().get(path) {
+        Some(stamp) => file_stamp(path).as_ref() == Some(stamp),
+        None => false,
+    }
+}
+
+// 记录导入失败的文件, 书库中已存在相同书本的文件同样视为失败
+fn record_result(result: &ImportResult) {
+    let mut rejected = REJECTED.lock().unwrap();
+
+    match result.status {
+        ImportStatus::Imported | ImportStatus::MissingCover => {
+            rejected.remove(&result.path);
+        }
+        _ => {
+            if let Some(stamp) = file_stamp(&result.path) {
+                rejected.insert(result.path.clone(), stamp);
+            }
+        }
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = fs::metadata(path).ok()?;
+
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+/// 导入单个文件, 每个阶段开始时调用 report
+///
+/// 书本信息在所有文件保存成功后才会写入数据库,
+/// 中途失败时会清理已保存的文件, 因此失败的文件可以再次导入
+pub fn import_book(path: &Path, mut report: impl FnMut(ImportStage)) -> ImportResult {
+    let result = import(path, &mut report);
+    report(ImportStage::Finished);
+
+    result
+}
+
+fn import(path: &Path, report: &mut impl FnMut(ImportStage)) -> ImportResult {
+    report(ImportStage::Parsing);
+    let result = ImportResult::new(path);
+
+    let format = match Format::detect(path) {
+        Some(format) => format,
+        None => return result.fail(ImportStatus::Unsupported, String::new()),
+    };
+
+    if !path.is_file() {
+        return result.fail(ImportStatus::Corrupt, "File not found".to_string());
+    }
+
+    let info = match format {
+        Format::Epub => BookInfo::new(path.to_path_buf()),
+        Format::Txt => BookInfo::new_txt(path.to_path_buf()),
+    };
+    let mut info = match info {
+        Ok(info) => info,
+        Err(err) => {
+            warn!("解析书本 {:?} 失败: {}", path, err);
+            return result.fail(ImportStatus::Corrupt, err.to_string());
+        }
+    };
+
+    let mut result = ImportResult {
+        title: info.title.clone(),
+        ..result
+    };
+
+    let saved = {
+        let _guard = IMPORT_LOCK.lock().unwrap();
+
+        if let Ok(exist) = BookInfo::get_specific_info(&info.id) {
+            relink(exist, path);
+            return result.fail(ImportStatus::Duplicate, String::new());
+        }
+
+        // 文件内容不同, 但标题, 作者与标识符相同的书本同样视为重复
+        match BookInfo::find_duplicate(&info) {
+            Ok(Some(exist)) => {
+                let reason = format!("Same as {}", exist.file_path.to_string_lossy());
+                return result.fail(ImportStatus::Duplicate, reason);
+            }
+            Ok(None) => {}
+            Err(err) => warn!("查找重复书本失败: {}", err),
+        }
+
+        // 失败时清理已保存的文件, 清理完成前不释放锁
+        report(ImportStage::Saving);
+        let saved = save(&mut info, path, format);
+        if saved.is_err() {
+            clean(&info);
+        }
+
+        saved
+    };
+
+    match saved {
+        Ok(status) => {
+            result.status = status;
+
+            report(ImportStage::Indexing);
+            index_book(&info);
+
+            result
+        }
+        Err(err) => {
+            warn!("保存书本 {} 失败: {}", info.title, err);
+
+            result.fail(ImportStatus::Corrupt, err.to_string())
+        }
+    }
+}
+
+// 保存书本文件与封面, 并写入书本信息
+fn save(info: &mut BookInfo, path: &Path, format: Format) -> Result<ImportStatus> {
+    let mut status = ImportStatus::Imported;
+
+    match format {
+        Format::Epub => {
+            let mut book = EpubDoc::new(path).map_err(|err| Error::Epub(err.to_string()))?;
+
+            if !save_cover(info, &mut book)? {
+                status = ImportStatus::MissingCover;
+            }
+            fs::copy(path, &info.file_path)?;
+            save_resources(info, &mut book)?;
+        }
+        Format::Txt => {
+            // 将 txt 文件转为 UTF-8 编码后保存
+            let text = decode_text(&fs::read(path)?);
+            fs::write(&info.file_path, text)?;
+        }
+    }
+
+    // txt 文件本身没有封面, 同样使用占位封面, 但不视为缺少封面
+    if info.cover_path.as_os_str().is_empty() {
+        info.cover_path = save_placeholder(info)?;
+    }
+    // 缩略图生成失败时书本列表直接使用封面, 不影响导入
+    if let Err(err) = save_thumbnails(&info.cover_path) {
+        warn!("生成书本 {} 的缩略图失败: {}", info.title, err);
+    }
+    info.thumbnails = thumbnail_paths(&info.cover_path);
+
+    info.last_open = time_stamp();
+    info.add_time = info.last_open;
+    BookInfo::insert_info(info)?;
+
+    Ok(status)
+}
+
+// 保存封面, 书本没有封面或封面无法读取时返回 false
+fn save_cover(info: &mut BookInfo, book: &mut EpubDoc<BufReader<File>>) -> Result<bool> {
+    if info.cover_path.as_os_str().is_empty() {
+        return Ok(false);
+    }
+
+    match book.get_cover() {
+        Some((cover, _)) if !cover.is_empty() => {
+            fs::write(&info.cover_path, cover)?;
+            Ok(true)
+        }
+        _ => {
+            info.cover_path = PathBuf::new();
+            Ok(false)
+        }
+    }
+}
+
+fn save_resources(info: &BookInfo, book: &mut EpubDoc<BufReader<File>>) -> Result<()> {
+    let resources = book.resources.clone();
+    let resources_path = resources_dir(info);
+
+    for (_, (path, mime)) in resources.iter() {
+        let is_image = match mime.parse::<mime::Mime>() {
+            Ok(mime) => mime.type_() == mime::IMAGE,
+            Err(_) => false,
+        };
+
+        if !is_image {
+            continue;
+        }
+
+        let image = resources_path.join(path);
+        if let Some(parent) = image.parent() {
+            create_dir_all(parent)?;
+        }
+
+        match book.get_resource_by_path(path) {
+            Some(buff) => fs::write(image, buff)?,
+            None => warn!("读取资源失败: {:?}", path),
+        }
+    }
+
+    Ok(())
+}
+
+/// 将书本内容写入全文索引并缓存章节字数, 失败时只影响搜索与阅读时间的估计, 不影响阅读
+pub fn index_book(info: &BookInfo) {
+    let mut book = match book::open(info) {
+        Ok(book) => book,
+        Err(err) => {
+            warn!("建立全文索引失败: {}, {}", info.title, err);
+            return;
+        }
+    };
+
+    let chapters = (0..book.page_count())
+        .map(|index| book.get_page_text(index))
+        .collect::<Vec<_>>();
+
+    if let Err(err) = SearchResult::insert_book(&info.id, &chapters) {
+        warn!("建立全文索引失败: {}, {}", info.title, err);
+    }
+    save_word_counts(info, &chapters);
+}
+
+/// 为尚未建立全文索引的书本建立索引, 早期版本导入的书本没有索引
+///
+/// 在程序启动时于后台调用, 同时缓存这些书本的章节字数
+pub fn index_library() {
+    let ids = match SearchResult::unindexed_books() {
+        Ok(ids) => ids,
+        Err(err) => {
+            warn!("查询未建立全文索引的书本失败: {}", err);
+            return;
+        }
+    };
+
+    for id in ids {
+        match BookInfo::get_specific_info(&id) {
+            Ok(info) => index_book(&info),
+            Err(err) => warn!("读取书本 {} 失败: {}", id, err),
+        }
+    }
+}
+
+/// 统计书本的章节字数并写入缓存, 失败时只影响阅读时间的估计
+pub fn count_book(info: &BookInfo) {
+    let mut book = match book::open(info) {
+        Ok(book) => book,
+        Err(err) => {
+            warn!("统计章节字数失败: {}, {}", info.title, err);
+            return;
+        }
+    };
+
+    let chapters = (0..book.page_count())
+        .map(|index| book.get_page_text(index))
+        .collect::<Vec<_>>();
+
+    save_word_counts(info, &chapters);
+}
+
+fn save_word_counts(info: &BookInfo, chapters: &[Vec<String>]) {
+    let counts = chapters
+        .iter()
+        .map(|chapter| count_words(chapter))
+        .collect::<Vec<_>>();
+
+    if let Err(err) = WordCount::save_book_counts(&info.id, &counts) {
+        warn!("保存章节字数失败: {}, {}", info.title, err);
+    }
+}
+
+// 原始文件被移动后再次导入时, 更新书本的原始文件路径
+fn relink(mut info: BookInfo, path: &Path) {
+    if info.source_path == path || (info.source_path.exists() && !info.missing) {
+        return;
+    }
+
+    info.source_path = path.to_path_buf();
+    info.missing = false;
+    if let Err(err) = BookInfo::update_info(&info) {
+        warn!("更新书本 {} 的原始文件路径失败: {}", info.title, err);
+    }
+}
+
+// 清理导入失败时已保存的文件
+fn clean(info: &BookInfo) {
+    let _ = fs::remove_file(&info.file_path);
+    remove_cover(&info.cover_path);
+    let _ = fs::remove_dir_all(resources_dir(info));
+}
+
+/// 书本图片资源的存储目录
+pub fn resources_dir(info: &BookInfo) -> PathBuf {
+    PathBuf::from(read_config().book.resources.clone()).join(info.id.as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        path::{Path, PathBuf},
+        sync::atomic::AtomicBool,
+    };
+
+    use crate::entity::import::ImportStage;
+
+    use super::{expand_paths, import_book, import_books, is_rejected, ImportStatus};
+
+    #[test]
+    fn status_test() {
+        let mut stages = Vec::new();
+        let result = import_book(Path::new("/not/exist/book.pdf"), |stage| stages.push(stage));
+        assert_eq!(result.status, ImportStatus::Unsupported);
+        assert_eq!(result.title, "book.pdf");
+        assert_eq!(stages, [ImportStage::Parsing, ImportStage::Finished]);
+
+        let result = import_book(Path::new("/not/exist/book.epub"), |_| {});
+        assert_eq!(result.status, ImportStatus::Corrupt);
+    }
+
+    #[test]
+    fn cancel_test() {
+        let paths = vec![PathBuf::from("a.pdf"), PathBuf::from("b.pdf")];
+
+        let mut percent = 0.0;
+        let summary = import_books(&paths, &AtomicBool::new(false), |progress| {
+            percent = progress.percent
+        });
+        assert!(!summary.cancelled);
+        assert_eq!(summary.results.len(), 2);
+        assert_eq!(percent, 100.0);
+
+        let summary = import_books(&paths, &AtomicBool::new(true), |_| {});
+        assert!(summary.cancelled);
+        assert!(summary.results.is_empty());
+    }
+
+    #[test]
+    fn rejected_test() {
+        let path = std::env::temp_dir().join("reader_rejected_test.pdf");
+        std::fs::write(&path, "not a book").unwrap();
+
+        import_books(std::slice::from_ref(&path), &AtomicBool::new(false), |_| {});
+        assert!(is_rejected(&path));
+
+        // 文件修改后可以再次自动导入
+        std::fs::write(&path, "still not a book").unwrap();
+        assert!(!is_rejected(&path));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn expand_test() {
+        let dir = std::env::temp_dir().join("reader_expand_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub/.hidden")).unwrap();
+        for name in ["a.epub", "b.jpg", "sub/c.TXT", "sub/.hidden/d.epub"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        let files = expand_paths(&[dir.clone(), PathBuf::from("e.pdf")]);
+        assert_eq!(
+            files,
+            [
+                dir.join("a.epub"),
+                dir.join("sub/c.TXT"),
+                PathBuf::from("e.pdf")
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
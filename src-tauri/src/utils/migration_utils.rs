@@ -0,0 +1,850 @@
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use epub::doc::EpubDoc;
+use log::{info, warn};
+use rusqlite::{params, Connection, Error as SqlError, OptionalExtension, Transaction};
+
+use crate::{
+    entity::{bookinfo::BookInfo, epub::Epub},
+    utils::{common_utils::hash, config_utils::read_config, cover_utils::save_thumbnails},
+};
+
+/// 数据库迁移
+///
+/// 版本号保存在 `PRAGMA user_version` 中, 迁移按版本号顺序执行,
+/// 每个迁移在独立的事务中执行, 失败时回滚且不更新版本号,
+/// 迁移中通过 MigrationContext 完成的文件修改同时撤销
+struct Migration {
+    version: u32,
+    description: &'static str,
+    up: fn(&Transaction, &mut MigrationContext) -> Result<(), MigrationError>,
+}
+
+/// 迁移的上下文
+#[derive(Debug, Default)]
+struct MigrationContext {
+    /// 迁移前的版本号
+    from: u32,
+
+    /// 迁移过程中重命名的文件, 迁移失败时按相反顺序恢复
+    renamed: Vec<(PathBuf, PathBuf)>,
+}
+
+impl MigrationContext {
+    fn rename(&mut self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)?;
+        self.renamed.push((from.to_path_buf(), to.to_path_buf()));
+
+        Ok(())
+    }
+
+    /// 撤销第 len 个之后的重命名
+    fn rollback_to(&mut self, len: usize) {
+        while self.renamed.len() > len {
+            let (from, to) = self.renamed.pop().unwrap();
+            if let Err(err) = fs::rename(&to, &from) {
+                warn!("恢复文件 {:?} 失败: {}", from, err);
+            }
+        }
+    }
+}
+
+/// 全部迁移, 新的迁移只能追加在末尾, 已发布的迁移不可修改
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "创建书本信息与书签表",
+        up: |tx, _| {
+            Ok(tx.execute_batch(
+                "CREATE TABLE IF NOT EXISTS book_info (
+                    id          TEXT    PRIMARY KEY,
+                    file_path   TEXT    NOT NULL,
+                    cover_path  TEXT    NOT NULL,
+                    title       TEXT    NOT NULL,
+                    creator     TEXT    NOT NULL,
+                    date        TEXT    NOT NULL,
+                    publisher   TEXT    NOT NULL,
+                    language    TEXT    NOT NULL,
+                    subject     TEXT    NOT NULL,
+                    description TEXT    NOT NULL,
+                    last_open   INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS book_mark (
+                    book_id         TEXT,
+                    mark_id         INTEGER PRIMARY KEY,
+                    start_chapter   INTEGER NOT NULL,
+                    start_paragraph INTEGER NOT NULL,
+                    start_offset    INTEGER NOT NULL,
+                    end_chapter     INTEGER NOT NULL,
+                    end_paragraph   INTEGER NOT NULL,
+                    end_offset      INTEGER NOT NULL,
+                    create_time     INTEGER NOT NULL
+                );",
+            )?)
+        },
+    },
+    Migration {
+        version: 2,
+        description: "书签添加批注相关字段",
+        up: |tx, _| {
+            add_column(tx, "book_mark", "text", "TEXT NOT NULL DEFAULT ''")?;
+            add_column(tx, "book_mark", "color", "TEXT NOT NULL DEFAULT ''")?;
+            add_column(tx, "book_mark", "style", "TEXT NOT NULL DEFAULT 'Highlight'")?;
+            add_column(tx, "book_mark", "note", "TEXT NOT NULL DEFAULT ''")?;
+            add_column(tx, "book_mark", "tags", "TEXT NOT NULL DEFAULT ''")?;
+            add_column(tx, "book_mark", "update_time", "INTEGER NOT NULL DEFAULT 0")
+        },
+    },
+    Migration {
+        version: 3,
+        description: "创建阅读进度与全文搜索表",
+        up: |tx, _| {
+            Ok(tx.execute_batch(
+                "CREATE TABLE IF NOT EXISTS reading_progress (
+                    book_id     TEXT    PRIMARY KEY,
+                    chapter     INTEGER NOT NULL,
+                    paragraph   INTEGER NOT NULL,
+                    offset      INTEGER NOT NULL,
+                    percentage  REAL    NOT NULL,
+                    update_time INTEGER NOT NULL
+                );
+                CREATE VIRTUAL TABLE IF NOT EXISTS book_search USING fts5 (
+                    book_id     UNINDEXED,
+                    chapter     UNINDEXED,
+                    paragraph   UNINDEXED,
+                    content,
+                    tokenize = 'trigram'
+                );",
+            )?)
+        },
+    },
+    Migration {
+        version: 4,
+        description: "书本信息添加原始文件路径",
+        up: |tx, _| {
+            add_column(tx, "book_info", "source_path", "TEXT NOT NULL DEFAULT ''")?;
+            add_column(tx, "book_info", "missing", "INTEGER NOT NULL DEFAULT 0")
+        },
+    },
+    Migration {
+        version: 5,
+        description: "书本信息添加标识符, 并重新计算书本 id",
+        up: |tx, context| {
+            add_column(tx, "book_info", "identifier", "TEXT NOT NULL DEFAULT ''")?;
+            rehash_books(tx, context)
+        },
+    },
+    Migration {
+        version: 6,
+        description: "书本信息添加系列",
+        up: |tx, _| {
+            add_column(tx, "book_info", "series", "TEXT NOT NULL DEFAULT ''")?;
+            add_column(tx, "book_info", "series_index", "REAL NOT NULL DEFAULT 0")
+        },
+    },
+    Migration {
+        version: 7,
+        description: "生成封面缩略图",
+        up: create_thumbnails,
+    },
+    Migration {
+        version: 8,
+        description: "创建书架与标签表",
+        up: |tx, _| {
+            Ok(tx.execute_batch(
+                "CREATE TABLE IF NOT EXISTS shelf (
+                    id          INTEGER PRIMARY KEY,
+                    name        TEXT    NOT NULL UNIQUE,
+                    create_time INTEGER NOT NULL
+                );
+                CREATE TABLE IF NOT EXISTS book_shelf (
+                    book_id     TEXT    NOT NULL,
+                    shelf_id    INTEGER NOT NULL,
+                    PRIMARY KEY (book_id, shelf_id)
+                );
+                CREATE TABLE IF NOT EXISTS book_tag (
+                    book_id     TEXT    NOT NULL,
+                    tag         TEXT    NOT NULL,
+                    PRIMARY KEY (book_id, tag)
+                );
+                CREATE INDEX IF NOT EXISTS book_tag_tag ON book_tag (tag);",
+            )?)
+        },
+    },
+    Migration {
+        version: 9,
+        description: "书本信息添加导入时间",
+        up: |tx, _| {
+            add_column(tx, "book_info", "add_time", "INTEGER NOT NULL DEFAULT 0")?;
+            // 已导入的书本没有记录导入时间, 使用最后打开的时间代替
+            tx.execute("UPDATE book_info SET add_time = last_open;", [])?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 10,
+        description: "创建阅读记录表",
+        up: |tx, _| {
+            Ok(tx.execute_batch(
+                "CREATE TABLE IF NOT EXISTS reading_session (
+                    id          INTEGER PRIMARY KEY,
+                    book_id     TEXT    NOT NULL,
+                    start_time  INTEGER NOT NULL,
+                    end_time    INTEGER NOT NULL,
+                    chapters    TEXT    NOT NULL,
+                    words       INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS reading_session_book ON reading_session (book_id);",
+            )?)
+        },
+    },
+    Migration {
+        version: 11,
+        description: "创建章节字数缓存表",
+        up: |tx, _| {
+            Ok(tx.execute_batch(
+                "CREATE TABLE IF NOT EXISTS chapter_words (
+                    book_id     TEXT    NOT NULL,
+                    chapter     INTEGER NOT NULL,
+                    words       INTEGER NOT NULL,
+                    characters  INTEGER NOT NULL,
+                    PRIMARY KEY (book_id, chapter)
+                );",
+            )?)
+        },
+    },
+    Migration {
+        version: 12,
+        description: "epub 书本的章节索引由目录项序号转换为 spine 索引",
+        up: |tx, context| match context.from {
+            // 未记录版本号的数据库由早期版本创建, 章节索引为目录项的序号
+            0 => convert_chapters(tx),
+            _ => Ok(()),
+        },
+    },
+    Migration {
+        version: 13,
+        description: "补充已导入书本的系列",
+        up: |tx, _| fill_series(tx),
+    },
+];
+
+#[derive(Debug)]
+pub enum MigrationError {
+    /// 数据库由更新版本的程序创建
+    Newer { current: u32, latest: u32 },
+
+    Sql(SqlError),
+
+    Io(io::Error),
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::Newer { current, latest } => write!(
+                f,
+                "数据库版本 {} 高于程序支持的版本 {}, 请升级程序",
+                current, latest
+            ),
+            MigrationError::Sql(err) => write!(f, "{}", err),
+            MigrationError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<SqlError> for MigrationError {
+    fn from(err: SqlError) -> Self {
+        MigrationError::Sql(err)
+    }
+}
+
+impl From<io::Error> for MigrationError {
+    fn from(err: io::Error) -> Self {
+        MigrationError::Io(err)
+    }
+}
+
+/// 程序支持的最新数据库版本
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
+
+pub fn schema_version(conn: &Connection) -> Result<u32, SqlError> {
+    conn.query_row("PRAGMA user_version;", [], |row| row.get(0))
+}
+
+/// 将数据库迁移到最新版本
+///
+/// 已有数据的数据库在迁移前会备份到同目录下的 `<文件名>.v<版本号>.bak`,
+/// 返回迁移前的版本号
+pub fn migrate(conn: &mut Connection, path: &Path) -> Result<u32, MigrationError> {
+    let current = schema_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        return Err(MigrationError::Newer { current, latest });
+    }
+
+    if current == latest {
+        return Ok(current);
+    }
+
+    if !is_empty(conn)? {
+        let backup = backup_path(path, current);
+        if backup.exists() {
+            warn!("覆盖已存在的数据库备份: {:?}", backup);
+            let _ = std::fs::remove_file(&backup);
+        }
+
+        conn.execute("VACUUM INTO ?1;", [backup.to_string_lossy()])?;
+        info!("数据库已备份到 {:?}", backup);
+    }
+
+    for migration in MIGRATIONS.iter().filter(|item| item.version > current) {
+        let mut context = MigrationContext {
+            from: current,
+            ..Default::default()
+        };
+        if let Err(err) = apply(conn, migration, &mut context) {
+            context.rollback_to(0);
+            return Err(err);
+        }
+
+        info!(
+            "数据库迁移到版本 {}: {}",
+            migration.version, migration.description
+        );
+    }
+
+    Ok(current)
+}
+
+fn apply(
+    conn: &mut Connection,
+    migration: &Migration,
+    context: &mut MigrationContext,
+) -> Result<(), MigrationError> {
+    let tx = conn.transaction()?;
+    (migration.up)(&tx, context)?;
+    tx.pragma_update(None, "user_version", migration.version)?;
+    tx.commit()?;
+
+    Ok(())
+}
+
+/// 数据库备份文件的路径
+pub fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".v{}.bak", version));
+
+    path.with_file_name(name)
+}
+
+fn is_empty(conn: &Connection) -> Result<bool, SqlError> {
+    let sql = "SELECT count(*) FROM sqlite_master WHERE type = 'table';";
+    let count: usize = conn.query_row(sql, [], |row| row.get(0))?;
+
+    Ok(count == 0)
+}
+
+/// 添加字段, 字段已存在时跳过
+///
+/// 早期版本没有记录版本号, 相同的字段可能已经存在
+fn add_column(
+    tx: &Transaction,
+    table_name: &str,
+    column_name: &str,
+    definition: &str,
+) -> Result<(), MigrationError> {
+    let sql = "SELECT count(*) FROM pragma_table_info(?1) WHERE name = ?2;";
+    let count: usize = tx.query_row(sql, [table_name, column_name], |row| row.get(0))?;
+
+    if count == 0 {
+        let sql = format!(
+            "ALTER TABLE {} ADD COLUMN {} {};",
+            table_name, column_name, definition
+        );
+        tx.execute(&sql, [])?;
+    }
+
+    Ok(())
+}
+
+/// 重新计算书本 id
+///
+/// 早期版本计算 hash 时包含了缓冲区中的残留数据, 相同的文件可能得到不同的 id.
+/// 修改 id 的同时修改书签, 阅读进度与全文索引中的 id,
+/// 以及书本文件, 封面与资源文件夹的文件名, 并补充 epub 的标识符.
+/// 没有原始文件的 txt 书本无法得到与重新导入一致的 id, 保留原有 id
+fn rehash_books(tx: &Transaction, context: &mut MigrationContext) -> Result<(), MigrationError> {
+    let books = {
+        let sql = "SELECT id, file_path, cover_path, source_path FROM book_info;";
+        let mut stmt = tx.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<usize, String>(0)?,
+                PathBuf::from(row.get::<usize, String>(1)?),
+                PathBuf::from(row.get::<usize, String>(2)?),
+                PathBuf::from(row.get::<usize, String>(3)?),
+            ))
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    if books.is_empty() {
+        return Ok(());
+    }
+    let resources = PathBuf::from(read_config().book.resources);
+
+    for (id, file_path, cover_path, source_path) in books {
+        if let Ok(book) = EpubDoc::new(&file_path) {
+            let identifier = book.mdata("identifier").unwrap_or_default();
+            let sql = "UPDATE book_info SET identifier = ?1 WHERE id = ?2;";
+            tx.execute(sql, [&identifier, &id])?;
+        }
+
+        // epub 文件为原样复制, 使用存储的文件; txt 文件保存时转换了编码, 使用原始文件
+        let is_txt = file_path.extension().is_some_and(|ext| ext == "txt");
+        let origin = match is_txt {
+            true if source_path.is_file() => &source_path,
+            true => continue,
+            false => &file_path,
+        };
+        let new_id = match hash(origin) {
+            Ok(new_id) => new_id,
+            Err(err) => {
+                warn!("重新计算书本 {} 的 id 失败: {}", id, err);
+                continue;
+            }
+        };
+
+        if new_id == id {
+            continue;
+        }
+
+        let sql = "SELECT count(*) FROM book_info WHERE id = ?1;";
+        let count: usize = tx.query_row(sql, [&new_id], |row| row.get(0))?;
+        if count > 0 {
+            warn!("书本 {} 与 {} 内容相同, 保留原有 id", id, new_id);
+            continue;
+        }
+
+        let renamed = rename_files(context, &id, &new_id, &file_path, &cover_path, &resources);
+        let (new_file, new_cover) = match renamed {
+            Ok(paths) => paths,
+            Err(err) => {
+                warn!("重命名书本 {} 的文件失败, 保留原有 id: {}", id, err);
+                continue;
+            }
+        };
+
+        let sql = "UPDATE book_info SET id = ?1, file_path = ?2, cover_path = ?3
+                   WHERE id = ?4;";
+        tx.execute(
+            sql,
+            params![
+                new_id,
+                new_file.to_string_lossy(),
+                new_cover.to_string_lossy(),
+                id
+            ],
+        )?;
+
+        for table in ["book_mark", "reading_progress", "book_search"] {
+            let sql = format!("UPDATE {} SET book_id = ?1 WHERE book_id = ?2;", table);
+            tx.execute(&sql, [&new_id, &id])?;
+        }
+
+        info!("书本 id 由 {} 修改为 {}", id, new_id);
+    }
+
+    Ok(())
+}
+
+/// 无法转换章节索引的书签添加的标签
+const INVALID_TAG: &str = "位置失效";
+
+/// 无法转换章节索引的书签使用的章节, 不会出现在任何章节中
+const INVALID_CHAPTER: i64 = i64::MAX;
+
+/// 将 epub 书本的书签, 阅读进度与全文索引中的章节索引由目录项序号转换为 spine 索引
+///
+/// 无法打开的书本, 阅读进度被重置, 书签移动到 INVALID_CHAPTER 并添加 INVALID_TAG 标签
+fn convert_chapters(tx: &Transaction) -> Result<(), MigrationError> {
+    let books = {
+        let mut stmt = tx.prepare("SELECT id, file_path FROM book_info;")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<usize, String>(0)?,
+                PathBuf::from(row.get::<usize, String>(1)?),
+            ))
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    for (id, file_path) in books {
+        let is_txt = file_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("txt"));
+        if is_txt {
+            continue;
+        }
+
+        let chapters = match Epub::legacy_chapters(&file_path) {
+            Ok(chapters) => chapters,
+            Err(err) => {
+                warn!("无法转换书本 {} 的章节索引, 重置阅读位置: {}", id, err);
+                Vec::new()
+            }
+        };
+
+        convert_marks(tx, &id, &chapters)?;
+        convert_progress(tx, &id, &chapters)?;
+        convert_search(tx, &id, &chapters)?;
+    }
+
+    Ok(())
+}
+
+fn convert_marks(tx: &Transaction, id: &str, chapters: &[usize]) -> Result<(), MigrationError> {
+    let marks = {
+        let sql = "SELECT mark_id, start_chapter, end_chapter, tags FROM book_mark
+                        WHERE book_id = ?1;";
+        let mut stmt = tx.prepare(sql)?;
+        let rows = stmt.query_map([id], |row| {
+            Ok((
+                row.get::<usize, i64>(0)?,
+                row.get::<usize, usize>(1)?,
+                row.get::<usize, usize>(2)?,
+                row.get::<usize, String>(3)?,
+            ))
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    for (mark_id, start, end, tags) in marks {
+        match (chapters.get(start), chapters.get(end)) {
+            (Some(start), Some(end)) => {
+                let sql = "UPDATE book_mark SET start_chapter = ?1, end_chapter = ?2
+                                WHERE mark_id = ?3;";
+                tx.execute(sql, params![start, end, mark_id])?;
+            }
+            _ => {
+                let tags = match tags.is_empty() {
+                    true => INVALID_TAG.to_string(),
+                    false => format!("{},{}", tags, INVALID_TAG),
+                };
+                let sql = "UPDATE book_mark SET start_chapter = ?1, end_chapter = ?1, tags = ?2
+                                WHERE mark_id = ?3;";
+                tx.execute(sql, params![INVALID_CHAPTER, tags, mark_id])?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn convert_progress(tx: &Transaction, id: &str, chapters: &[usize]) -> Result<(), MigrationError> {
+    let sql = "SELECT chapter FROM reading_progress WHERE book_id = ?1;";
+    let chapter: Option<usize> = tx.query_row(sql, [id], |row| row.get(0)).optional()?;
+
+    match chapter.map(|chapter| chapters.get(chapter)) {
+        None => {}
+        Some(Some(target)) => {
+            let sql = "UPDATE reading_progress SET chapter = ?1 WHERE book_id = ?2;";
+            tx.execute(sql, params![target, id])?;
+        }
+        Some(None) => {
+            tx.execute("DELETE FROM reading_progress WHERE book_id = ?1;", [id])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 早期版本按目录项建立索引, 多个目录项指向同一页面时该页面被重复索引,
+/// 只保留第一个目录项的索引
+fn convert_search(tx: &Transaction, id: &str, chapters: &[usize]) -> Result<(), MigrationError> {
+    // 先将章节索引改为负数, 避免转换前后的索引冲突
+    let sql = "UPDATE book_search SET chapter = -1 - chapter WHERE book_id = ?1;";
+    tx.execute(sql, [id])?;
+
+    let mut converted = Vec::new();
+    for (index, target) in chapters.iter().enumerate() {
+        let legacy = -1 - index as i64;
+        if converted.contains(target) {
+            let sql = "DELETE FROM book_search WHERE book_id = ?1 AND chapter = ?2;";
+            tx.execute(sql, params![id, legacy])?;
+        } else {
+            let sql = "UPDATE book_search SET chapter = ?1 WHERE book_id = ?2 AND chapter = ?3;";
+            tx.execute(sql, params![target, id, legacy])?;
+            converted.push(*target);
+        }
+    }
+
+    let sql = "DELETE FROM book_search WHERE book_id = ?1 AND chapter < 0;";
+    tx.execute(sql, [id])?;
+
+    Ok(())
+}
+
+/// 从 epub 文件中读取没有系列的书本的系列, 读取失败时只记录日志
+///
+/// 早期版本导入书本时没有读取系列
+fn fill_series(tx: &Transaction) -> Result<(), MigrationError> {
+    let books = {
+        let sql = "SELECT id, file_path FROM book_info WHERE series = '';";
+        let mut stmt = tx.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<usize, String>(0)?,
+                PathBuf::from(row.get::<usize, String>(1)?),
+            ))
+        })?;
+
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    for (id, file_path) in books {
+        let is_epub = file_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"));
+        if !is_epub {
+            continue;
+        }
+
+        match BookInfo::read_series(&file_path) {
+            Ok((series, _)) if series.is_empty() => {}
+            Ok((series, index)) => {
+                let sql = "UPDATE book_info SET series = ?1, series_index = ?2 WHERE id = ?3;";
+                tx.execute(sql, params![series, index, id])?;
+            }
+            Err(err) => warn!("读取书本 {} 的系列失败: {}", id, err),
+        }
+    }
+
+    Ok(())
+}
+
+/// 为已导入的书本生成封面缩略图, 生成失败时只记录日志
+fn create_thumbnails(tx: &Transaction, _: &mut MigrationContext) -> Result<(), MigrationError> {
+    let covers = {
+        let mut stmt = tx.prepare("SELECT cover_path FROM book_info;")?;
+        let rows = stmt.query_map([], |row| row.get::<usize, String>(0))?;
+
+        rows.collect::<Result<Vec<_>, _>>()?
+    };
+
+    for cover in covers {
+        if let Err(err) = save_thumbnails(Path::new(&cover)) {
+            warn!("生成封面 {} 的缩略图失败: {}", cover, err);
+        }
+    }
+
+    Ok(())
+}
+
+/// 将以旧 id 命名的文件重命名为新 id, 返回新的书本与封面路径
+///
+/// 任一文件重命名失败时撤销该书本已完成的重命名
+fn rename_files(
+    context: &mut MigrationContext,
+    id: &str,
+    new_id: &str,
+    file_path: &Path,
+    cover_path: &Path,
+    resources: &Path,
+) -> io::Result<(PathBuf, PathBuf)> {
+    let len = context.renamed.len();
+
+    let mut targets = vec![
+        (file_path.to_path_buf(), with_stem(file_path, id, new_id)),
+        (cover_path.to_path_buf(), with_stem(cover_path, id, new_id)),
+        (resources.join(id), resources.join(new_id)),
+    ];
+    targets.retain(|(from, to)| from != to && from.exists());
+
+    for (from, to) in targets {
+        if let Err(err) = context.rename(&from, &to) {
+            context.rollback_to(len);
+            return Err(err);
+        }
+    }
+
+    Ok((
+        with_stem(file_path, id, new_id),
+        with_stem(cover_path, id, new_id),
+    ))
+}
+
+// 文件名(不含扩展名)与 id 相同时替换为新的 id
+fn with_stem(path: &Path, id: &str, new_id: &str) -> PathBuf {
+    if !matches!(path.file_stem(), Some(stem) if stem == id) {
+        return path.to_path_buf();
+    }
+
+    match path.extension() {
+        Some(ext) => path.with_file_name(format!("{}.{}", new_id, ext.to_string_lossy())),
+        None => path.with_file_name(new_id),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, path::Path};
+
+    use rusqlite::Connection;
+
+    use super::{
+        backup_path, convert_marks, convert_progress, convert_search, latest_version, migrate,
+        schema_version, with_stem, MigrationContext, MigrationError, INVALID_CHAPTER,
+    };
+
+    #[test]
+    fn migrate_test() {
+        let path = env::temp_dir().join("reader_migrate_test.sqlite");
+        let backup = backup_path(&path, 0);
+
+        // 空数据库直接迁移到最新版本, 不需要备份
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(migrate(&mut conn, &path).unwrap(), 0);
+        assert_eq!(schema_version(&conn).unwrap(), latest_version());
+        assert!(!backup.exists());
+
+        // 再次迁移不做任何修改
+        assert_eq!(migrate(&mut conn, &path).unwrap(), latest_version());
+
+        // 更新版本的数据库拒绝打开
+        conn.pragma_update(None, "user_version", latest_version() + 1)
+            .unwrap();
+        assert!(matches!(
+            migrate(&mut conn, &path),
+            Err(MigrationError::Newer { .. })
+        ));
+    }
+
+    #[test]
+    fn legacy_test() {
+        let path = env::temp_dir().join("reader_legacy_test.sqlite");
+        let backup = backup_path(&path, 0);
+        let _ = std::fs::remove_file(&backup);
+
+        // 未记录版本号的旧数据库
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE book_mark (
+                book_id         TEXT,
+                mark_id         INTEGER PRIMARY KEY,
+                start_chapter   INTEGER NOT NULL,
+                start_paragraph INTEGER NOT NULL,
+                start_offset    INTEGER NOT NULL,
+                end_chapter     INTEGER NOT NULL,
+                end_paragraph   INTEGER NOT NULL,
+                end_offset      INTEGER NOT NULL,
+                create_time     INTEGER NOT NULL,
+                text            TEXT    NOT NULL DEFAULT ''
+            );
+            INSERT INTO book_mark VALUES ('id', 1, 0, 0, 0, 0, 0, 5, 0, 'hello');",
+        )
+        .unwrap();
+
+        migrate(&mut conn, &path).unwrap();
+        assert!(backup.exists());
+
+        let (text, style): (String, String) = conn
+            .query_row("SELECT text, style FROM book_mark WHERE mark_id = 1;", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(text, "hello");
+        assert_eq!(style, "Highlight");
+
+        let _ = std::fs::remove_file(&backup);
+    }
+
+    #[test]
+    fn stem_test() {
+        assert_eq!(
+            with_stem(Path::new("/book/old.epub"), "old", "new"),
+            Path::new("/book/new.epub")
+        );
+        assert_eq!(
+            with_stem(Path::new("/cover/other.png"), "old", "new"),
+            Path::new("/cover/other.png")
+        );
+        assert_eq!(with_stem(Path::new(""), "old", "new"), Path::new(""));
+    }
+
+    #[test]
+    fn rollback_test() {
+        let dir = env::temp_dir().join("reader_rollback_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (a, b, c) = (dir.join("a"), dir.join("b"), dir.join("c"));
+        std::fs::write(&a, "a").unwrap();
+
+        let mut context = MigrationContext::default();
+        context.rename(&a, &b).unwrap();
+        context.rename(&b, &c).unwrap();
+        context.rollback_to(1);
+        assert!(b.exists() && !c.exists());
+
+        context.rollback_to(0);
+        assert!(a.exists() && !b.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn convert_test() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn, Path::new("")).unwrap();
+        conn.execute_batch(
+            "INSERT INTO book_mark (book_id, mark_id, start_chapter, start_paragraph, start_offset,
+                end_chapter, end_paragraph, end_offset, create_time)
+                VALUES ('id', 1, 1, 0, 0, 3, 0, 0, 0), ('id', 2, 1, 0, 0, 4, 0, 0, 0);
+            INSERT INTO reading_progress VALUES ('id', 3, 0, 0, 50, 0);
+            INSERT INTO book_search VALUES ('id', 0, 0, 'a'), ('id', 1, 0, 'b'),
+                ('id', 2, 0, 'b'), ('id', 3, 0, 'c');",
+        )
+        .unwrap();
+
+        // 目录项 1 与 2 指向同一页面
+        let chapters = [0, 2, 2, 5];
+        let tx = conn.transaction().unwrap();
+        convert_marks(&tx, "id", &chapters).unwrap();
+        convert_progress(&tx, "id", &chapters).unwrap();
+        convert_search(&tx, "id", &chapters).unwrap();
+        tx.commit().unwrap();
+
+        let query = |sql: &str| -> Vec<(i64, String)> {
+            let mut stmt = conn.prepare(sql).unwrap();
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)));
+            rows.unwrap().map(|row| row.unwrap()).collect()
+        };
+
+        let marks = query("SELECT end_chapter, tags FROM book_mark ORDER BY mark_id;");
+        assert_eq!(marks[0], (5, String::new()));
+        // 超出目录范围的书签标记为失效
+        assert_eq!(marks[1].0, INVALID_CHAPTER);
+        assert!(!marks[1].1.is_empty());
+
+        let progress = query("SELECT chapter, book_id FROM reading_progress;");
+        assert_eq!(progress, vec![(5, "id".to_string())]);
+
+        let search = query("SELECT chapter, content FROM book_search ORDER BY chapter;");
+        let expected = [(0, "a"), (2, "b"), (5, "c")]
+            .iter()
+            .map(|(chapter, content)| (*chapter, content.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(search, expected);
+    }
+}
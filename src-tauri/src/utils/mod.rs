@@ -1,3 +1,12 @@
 pub mod common_utils;
 pub mod config_utils;
+pub mod content_utils;
+pub mod cover_utils;
+pub mod export_utils;
+pub mod import_utils;
+pub mod migration_utils;
+pub mod opf_utils;
 pub mod resource_utils;
+pub mod session_utils;
+pub mod trash_utils;
+pub mod watch_utils;
@@ -0,0 +1,334 @@
+//! 将编辑后的元数据写回 epub 文件中的 OPF
+use std::{
+    fmt::Display,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use epub::doc::EpubDoc;
+use xml::{
+    reader::{EventReader, XmlEvent as ReadEvent},
+    writer::{EmitterConfig, EventWriter, XmlEvent as WriteEvent},
+};
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    entity::bookinfo::BookMetadata,
+    error::{Error, Result},
+};
+
+const DC_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+
+/// 将元数据写入 epub 文件
+///
+/// 第一次修改时将原始文件备份为 `<文件名>.orig`, 之后的修改不再覆盖备份,
+/// 返回备份文件的路径
+pub fn write_metadata(path: &Path, metadata: &BookMetadata) -> Result<PathBuf> {
+    let opf_path = EpubDoc::new(path)
+        .map_err(|err| Error::Epub(err.to_string()))?
+        .root_file
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let mut archive = ZipArchive::new(File::open(path)?).map_err(zip_error)?;
+
+    let mut opf = Vec::new();
+    archive
+        .by_name(&opf_path)
+        .map_err(zip_error)?
+        .read_to_end(&mut opf)?;
+    let opf = rewrite_opf(&opf, metadata)?;
+
+    // 先写入临时文件, 全部写入成功后再替换原文件, 失败时删除临时文件
+    let temp = with_suffix(path, ".tmp");
+    let result = write_archive(&mut archive, &temp, &opf_path, &opf).and_then(|_| {
+        let backup = backup_path(path);
+        if !backup.exists() {
+            fs::copy(path, &backup)?;
+        }
+        fs::rename(&temp, path)?;
+
+        Ok(backup)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+
+    result
+}
+
+/// 第一次写入元数据前备份的原始文件的路径
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".orig")
+}
+
+// 复制压缩包中的全部文件到 temp, 并替换其中的 OPF
+fn write_archive(
+    archive: &mut ZipArchive<File>,
+    temp: &Path,
+    opf_path: &str,
+    opf: &[u8],
+) -> Result<()> {
+    let mut writer = ZipWriter::new(File::create(temp)?);
+
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index).map_err(zip_error)?;
+
+        if file.name() == opf_path {
+            let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+            writer.start_file(opf_path, options).map_err(zip_error)?;
+            writer.write_all(opf)?;
+        } else {
+            writer.raw_copy_file(file).map_err(zip_error)?;
+        }
+    }
+
+    writer.finish().map_err(zip_error)?;
+
+    Ok(())
+}
+
+/// 修改 OPF 中的元数据, 返回修改后的内容
+///
+/// 逐个事件复制 OPF, 已存在的元素只替换文本以保留其属性,
+/// 值为空时删除对应的元素, 新增的元素添加在 metadata 的末尾
+pub fn rewrite_opf(content: &[u8], metadata: &BookMetadata) -> Result<Vec<u8>> {
+    let mut values = [
+        ("title", split(&metadata.title, false)),
+        ("creator", split(&metadata.creator, true)),
+        ("date", split(&metadata.date, false)),
+        ("publisher", split(&metadata.publisher, false)),
+        ("language", split(&metadata.language, false)),
+        ("subject", split(&metadata.subject, true)),
+        ("description", split(&metadata.description, false)),
+    ];
+
+    let series = metadata.series.trim().to_string();
+    let index = match series.is_empty() {
+        true => String::new(),
+        false => metadata.series_index.to_string(),
+    };
+    let metas = [("calibre:series", series), ("calibre:series_index", index)];
+
+    let mut buffer = Vec::new();
+    let mut writer = EmitterConfig::new().create_writer(&mut buffer);
+
+    // 当前元素的层级, metadata 元素所在的层级, metadata 处理完成后为 None
+    let mut depth = 0;
+    let mut metadata_depth = None;
+    let mut finished = false;
+    // 跳过原元素的内容时, 记录跳过的层级与是否需要写入结束标签
+    let mut skipping: Option<(usize, bool)> = None;
+
+    for event in EventReader::new(content) {
+        let event = event.map_err(xml_error)?;
+
+        if let Some((skip_depth, write_end)) = skipping.as_mut() {
+            match event {
+                ReadEvent::StartElement { .. } => *skip_depth += 1,
+                ReadEvent::EndElement { .. } => {
+                    *skip_depth -= 1;
+                    if *skip_depth == 0 {
+                        if *write_end {
+                            writer.write(WriteEvent::end_element()).map_err(xml_error)?;
+                        }
+                        skipping = None;
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match &event {
+            ReadEvent::StartElement {
+                name, attributes, ..
+            } => {
+                if metadata_depth == Some(depth) {
+                    if name.namespace.as_deref() == Some(DC_NAMESPACE) {
+                        if let Some((_, list)) =
+                            values.iter_mut().find(|(key, _)| *key == name.local_name)
+                        {
+                            // 按顺序使用新的值替换原有的值
+                            if list.is_empty() {
+                                skipping = Some((1, false));
+                            } else {
+                                let value = list.remove(0);
+                                write_event(&mut writer, &event)?;
+                                writer
+                                    .write(WriteEvent::characters(&value))
+                                    .map_err(xml_error)?;
+                                skipping = Some((1, true));
+                            }
+                            continue;
+                        }
+                    }
+
+                    let is_replaced = name.local_name == "meta"
+                        && attributes.iter().any(|attr| {
+                            attr.name.local_name == "name"
+                                && metas.iter().any(|(key, _)| *key == attr.value)
+                        });
+                    if is_replaced {
+                        skipping = Some((1, false));
+                        continue;
+                    }
+                }
+
+                depth += 1;
+                if name.local_name == "metadata" && !finished {
+                    metadata_depth = Some(depth);
+                }
+            }
+            ReadEvent::EndElement { .. } => {
+                if metadata_depth == Some(depth) {
+                    append_metadata(&mut writer, &values, &metas)?;
+                    metadata_depth = None;
+                    finished = true;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+
+        write_event(&mut writer, &event)?;
+    }
+
+    Ok(buffer)
+}
+
+// 新增元素前后的空白, 与常见 OPF 的缩进保持一致
+const INDENT: &str = "  ";
+const NEW_LINE: &str = "\n  ";
+
+// 在 metadata 的末尾写入剩余的新值
+fn append_metadata<W: Write>(
+    writer: &mut EventWriter<W>,
+    values: &[(&str, Vec<String>)],
+    metas: &[(&str, String)],
+) -> Result<()> {
+    for (name, list) in values {
+        for value in list {
+            let name = format!("dc:{}", name);
+
+            writer
+                .write(WriteEvent::characters(INDENT))
+                .map_err(xml_error)?;
+            writer
+                .write(WriteEvent::start_element(name.as_str()))
+                .map_err(xml_error)?;
+            writer
+                .write(WriteEvent::characters(value))
+                .map_err(xml_error)?;
+            writer.write(WriteEvent::end_element()).map_err(xml_error)?;
+            writer
+                .write(WriteEvent::characters(NEW_LINE))
+                .map_err(xml_error)?;
+        }
+    }
+
+    for (name, content) in metas.iter().filter(|(_, content)| !content.is_empty()) {
+        writer
+            .write(WriteEvent::characters(INDENT))
+            .map_err(xml_error)?;
+        writer
+            .write(
+                WriteEvent::start_element("meta")
+                    .attr("name", name)
+                    .attr("content", content),
+            )
+            .map_err(xml_error)?;
+        writer.write(WriteEvent::end_element()).map_err(xml_error)?;
+        writer
+            .write(WriteEvent::characters(NEW_LINE))
+            .map_err(xml_error)?;
+    }
+
+    Ok(())
+}
+
+fn write_event<W: Write>(writer: &mut EventWriter<W>, event: &ReadEvent) -> Result<()> {
+    if let Some(event) = event.as_writer_event() {
+        writer.write(event).map_err(xml_error)?;
+    }
+
+    Ok(())
+}
+
+// 去除空白并忽略空值, multiple 为 true 时以逗号分隔为多个值
+fn split(value: &str, multiple: bool) -> Vec<String> {
+    let values = match multiple {
+        true => value.split(',').collect(),
+        false => vec![value],
+    };
+
+    values
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+
+    path.with_file_name(name)
+}
+
+fn zip_error(err: zip::result::ZipError) -> Error {
+    Error::Epub(err.to_string())
+}
+
+fn xml_error(err: impl Display) -> Error {
+    Error::Epub(err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::entity::bookinfo::BookMetadata;
+
+    use super::rewrite_opf;
+
+    const OPF: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="uid">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+    <dc:title>Old Title</dc:title>
+    <dc:creator opf:role="aut">Old Author</dc:creator>
+    <dc:identifier id="uid">urn:isbn:9781718500440</dc:identifier>
+    <dc:subject>Old</dc:subject>
+    <meta name="calibre:series" content="Old Series"/>
+    <meta name="cover" content="cover-image"/>
+  </metadata>
+  <manifest/>
+  <spine/>
+</package>"#;
+
+    #[test]
+    fn rewrite_test() {
+        let metadata = BookMetadata {
+            title: "New & Title".to_string(),
+            creator: "Alice, Bob".to_string(),
+            language: "en".to_string(),
+            series: "Rust".to_string(),
+            series_index: 2.0,
+            ..Default::default()
+        };
+
+        let opf = String::from_utf8(rewrite_opf(OPF.as_bytes(), &metadata).unwrap()).unwrap();
+
+        assert!(opf.contains("New &amp; Title"));
+        assert!(!opf.contains("Old Title"));
+        // 已存在的元素保留属性
+        assert!(opf.contains(r#"opf:role="aut">Alice</dc:creator>"#));
+        assert!(opf.contains("Bob</dc:creator>"));
+        assert!(opf.contains("en</dc:language>"));
+        // 值为空的元素被删除, 其他元素保持不变
+        assert!(!opf.contains("dc:subject"));
+        assert!(opf.contains("urn:isbn:9781718500440"));
+        assert!(opf.contains(r#"content="cover-image""#));
+        assert!(opf.contains(r#"content="Rust""#));
+        assert!(!opf.contains("Old Series"));
+    }
+}
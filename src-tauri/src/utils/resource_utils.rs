@@ -1,5 +1,4 @@
 use std::{
-    collections::HashMap,
     env,
     fs::{create_dir_all, File},
     io::Write,
@@ -9,9 +8,9 @@ use std::{
 use log::{error, info};
 use rusqlite::Connection;
 
-use crate::utils::config_utils::Config;
+use crate::utils::{config_utils::Config, migration_utils::migrate};
 
-const DIR_LIST: [&str; 3] = ["book", "cover", "resources"];
+const DIR_LIST: [&str; 4] = ["book", "cover", "resources", "trash"];
 
 #[cfg(target_os = "windows")]
 fn data_dir() -> PathBuf {
@@ -29,6 +28,14 @@ fn data_dir() -> PathBuf {
     path
 }
 
+/// 回收站目录, 删除的书本在此保留一段时间
+pub fn trash_dir() -> PathBuf {
+    let mut path = data_dir();
+    path.push("trash");
+
+    path
+}
+
 /// 资源完整性检查
 pub fn resource_integrity_check() {
     let mut dir = data_dir();
@@ -98,7 +105,7 @@ fn config_check(path: &mut PathBuf) {
 fn database_check(path: &mut PathBuf) {
     path.push("data.sqlite");
 
-    let conn = match Connection::open(&path) {
+    let mut conn = match Connection::open(&path) {
         Ok(conn) => conn,
         Err(err) => {
             error!("打开数据库失败: {}", err);
@@ -106,63 +113,12 @@ fn database_check(path: &mut PathBuf) {
         }
     };
 
-    let mut tables: HashMap<&str, &str> = HashMap::new();
-    tables.insert(
-        "book_info",
-        "CREATE TABLE book_info (
-                id          TEXT    PRIMARY KEY,
-                file_path   TEXT    NOT NULL,
-                cover_path  TEXT    NOT NULL,
-                title       TEXT    NOT NULL,
-                creator     TEXT    NOT NULL,
-                date        TEXT    NOT NULL,
-                publisher   TEXT    NOT NULL,
-                language    TEXT    NOT NULL,
-                subject     TEXT    NOT NULL,
-                description TEXT    NOT NULL,
-                last_open   INTEGER NOT NULL
-            );",
-    );
-    tables.insert(
-        "book_mark",
-        "CREATE TABLE book_mark (
-                book_id         TEXT,
-                mark_id         INTEGER PRIMARY KEY,
-                start_chapter   INTEGER NOT NULL,
-                start_paragraph INTEGER NOT NULL,
-                start_offset    INTEGER NOT NULL,
-                end_chapter     INTEGER NOT NULL,
-                end_paragraph   INTEGER NOT NULL,
-                end_offset      INTEGER NOT NULL,
-                create_time     INTEGER NOT NULL
-            );",
-    );
-
-    for (name, sql) in tables {
-        if table_check(&conn, name) {
-            continue;
-        }
-
-        conn.execute(sql, []).unwrap_or_else(|err| {
-            error!("创建表 {} 失败: {}", name, err);
-            panic!();
-        });
+    if let Err(err) = migrate(&mut conn, path) {
+        error!("数据库迁移失败: {}", err);
+        panic!("{}", err);
     }
 
     info!("数据库完整性检查通过");
     let _ = conn.close();
     path.pop();
 }
-
-fn table_check(conn: &Connection, table_name: &str) -> bool {
-    let sql = "SELECT name FROM sqlite_master WHERE type='table' AND name= ?1";
-
-    let mut stmt = conn.prepare(sql).unwrap();
-    let mut rows = stmt.query([table_name]).unwrap();
-
-    if let Some(_) = rows.next().unwrap() {
-        return true;
-    }
-
-    false
-}
@@ -0,0 +1,72 @@
+//! 记录阅读会话
+//!
+//! 打开书本, 翻页, 跳转章节, 上报阅读进度与前端的心跳都视为阅读活动,
+//! 两次活动的间隔超过 IDLE_TIMEOUT 时视为离开, 下一次活动开始新的会话
+use std::sync::{LazyLock, Mutex};
+
+use log::warn;
+
+use crate::{
+    entity::{book::Book, session::ReadingSession},
+    utils::{common_utils::time_stamp, content_utils::count_words},
+};
+
+/// 两次阅读活动的最大间隔, 单位为毫秒
+const IDLE_TIMEOUT: u64 = 5 * 60 * 1000;
+
+/// 当前的阅读会话
+static SESSION: LazyLock<Mutex<Option<ReadingSession>>> = LazyLock::new(|| Mutex::new(None));
+
+/// 开始阅读书本, 结束之前的会话并记录当前的章节
+pub fn start_session(book: &mut dyn Book) {
+    end_session();
+    record_chapter(book);
+}
+
+/// 结束当前的会话, 会话在每次活动时已保存
+pub fn end_session() {
+    *SESSION.lock().unwrap() = None;
+}
+
+/// 记录当前的章节, 章节在本次会话中第一次访问时计入词数
+pub fn record_chapter(book: &mut dyn Book) {
+    let id = book.info().id.clone();
+    let chapter = book.current_page();
+
+    update(&id, |session| {
+        if !session.chapters.contains(&chapter) {
+            session.chapters.push(chapter);
+            session.words += count_words(&book.get_page_text(chapter)).total();
+        }
+    });
+}
+
+/// 记录一次没有切换章节的阅读活动
+pub fn record_activity(book_id: &str) {
+    update(book_id, |_| {});
+}
+
+fn update(book_id: &str, f: impl FnOnce(&mut ReadingSession)) {
+    let now = time_stamp();
+    let mut container = SESSION.lock().unwrap();
+
+    let expired = match container.as_ref() {
+        Some(session) => {
+            session.book_id != book_id || now.saturating_sub(session.end_time) > IDLE_TIMEOUT
+        }
+        None => true,
+    };
+    if expired {
+        *container = Some(ReadingSession::new(book_id, now));
+    }
+
+    let Some(session) = container.as_mut() else {
+        return;
+    };
+    session.end_time = now;
+    f(session);
+
+    if let Err(err) = session.save_session() {
+        warn!("保存阅读记录失败: {}, {}", book_id, err);
+    }
+}
@@ -0,0 +1,227 @@
+//! 回收站
+//!
+//! 每本删除的书本在回收站中占用一个以书本 id 命名的文件夹,
+//! 其中保存书本文件, 封面, 资源文件夹与记录书本数据的 item.json
+use std::{
+    cmp::Reverse,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::{info, warn};
+
+use crate::{
+    entity::{bookinfo::BookInfo, trash::TrashItem},
+    error::{Error, Result},
+    utils::{
+        common_utils::time_stamp,
+        cover_utils::{cover_files, remove_cover},
+        import_utils::resources_dir,
+        opf_utils::backup_path,
+        resource_utils::trash_dir,
+    },
+};
+
+const ITEM_FILE: &str = "item.json";
+const RESOURCES_DIR: &str = "resources";
+
+/// 一天的毫秒数
+const DAY: u64 = 24 * 60 * 60 * 1000;
+
+/// 将书本的文件移动到回收站, 并保存书本数据
+///
+/// 失败时已移动的文件会被移回原位置
+pub fn move_to_trash(item: &TrashItem) -> Result<()> {
+    let dir = trash_dir().join(&item.info.id);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+
+    let content = serde_json::to_string(item)?;
+    let moved = move_files(file_pairs(&item.info, &dir))?;
+
+    // 保存书本数据失败时将文件移回原位置
+    if let Err(err) = fs::write(dir.join(ITEM_FILE), content) {
+        undo_moves(&moved);
+        let _ = fs::remove_dir_all(&dir);
+        return Err(err.into());
+    }
+
+    Ok(())
+}
+
+/// 将书本的文件从回收站移回原位置, 返回书本数据
+///
+/// 书本数据需要由调用者重新写入数据库, 完成后使用 clear_trash 删除回收站中的文件夹
+pub fn restore_from_trash(id: &str) -> Result<TrashItem> {
+    let dir = trash_dir().join(id);
+    let item = read_item(&dir)?;
+
+    let pairs = file_pairs(&item.info, &dir)
+        .into_iter()
+        .map(|(to, from)| (from, to))
+        .collect();
+    move_files(pairs)?;
+
+    Ok(item)
+}
+
+/// 删除书本在回收站中的文件夹, 删除失败时只记录日志
+pub fn clear_trash(id: &str) {
+    let dir = trash_dir().join(id);
+
+    if let Err(err) = fs::remove_dir_all(&dir) {
+        warn!("删除回收站文件夹 {:?} 失败: {}", dir, err);
+    }
+}
+
+/// 回收站中的全部书本, 按删除时间倒序排列
+pub fn trash_list() -> Result<Vec<TrashItem>> {
+    let mut list = Vec::new();
+
+    for entry in fs::read_dir(trash_dir())? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        match read_item(&path) {
+            Ok(item) => list.push(item),
+            Err(err) => warn!("读取回收站中的书本 {:?} 失败: {}", path, err),
+        }
+    }
+
+    list.sort_by_key(|item| Reverse(item.deleted_time));
+
+    Ok(list)
+}
+
+/// 清理回收站中超过保留天数的书本, 返回清理的数量
+pub fn purge_trash(days: u32) -> usize {
+    let list = match trash_list() {
+        Ok(list) => list,
+        Err(err) => {
+            warn!("读取回收站失败: {}", err);
+            return 0;
+        }
+    };
+
+    let now = time_stamp();
+    let mut count = 0;
+    for item in list {
+        if item.deleted_time + days as u64 * DAY > now {
+            continue;
+        }
+
+        match fs::remove_dir_all(trash_dir().join(&item.info.id)) {
+            Ok(_) => count += 1,
+            Err(err) => warn!("清理回收站中的书本 {} 失败: {}", item.info.title, err),
+        }
+    }
+
+    if count > 0 {
+        info!("已清理回收站中的 {} 本书", count);
+    }
+
+    count
+}
+
+/// 直接删除书本的文件, 删除失败时只记录日志
+pub fn remove_files(info: &BookInfo) {
+    let resources = resources_dir(info);
+    if resources.exists() {
+        if let Err(err) = fs::remove_dir_all(&resources) {
+            warn!("删除资源文件夹 {:?} 失败: {}", resources, err);
+        }
+    }
+
+    for path in [info.file_path.clone(), backup_path(&info.file_path)] {
+        if path.exists() {
+            if let Err(err) = fs::remove_file(&path) {
+                warn!("删除文件 {:?} 失败: {}", path, err);
+            }
+        }
+    }
+    remove_cover(&info.cover_path);
+}
+
+// 移动存在的文件, 任一文件移动失败时撤销已完成的移动, 返回已移动的文件
+fn move_files(pairs: Vec<(PathBuf, PathBuf)>) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut moved = Vec::new();
+
+    for (from, to) in pairs {
+        if !from.exists() {
+            continue;
+        }
+
+        if let Err(err) = fs::rename(&from, &to) {
+            undo_moves(&moved);
+            return Err(err.into());
+        }
+        moved.push((from, to));
+    }
+
+    Ok(moved)
+}
+
+fn undo_moves(moved: &[(PathBuf, PathBuf)]) {
+    for (from, to) in moved.iter().rev() {
+        if let Err(err) = fs::rename(to, from) {
+            warn!("将文件 {:?} 移回原位置失败: {}", from, err);
+        }
+    }
+}
+
+fn read_item(dir: &Path) -> Result<TrashItem> {
+    let path = dir.join(ITEM_FILE);
+    if !path.exists() {
+        return Err(Error::NotFound("Trash item".to_string()));
+    }
+
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+// 书本文件在书库中的路径与在回收站中的路径, 包含编辑元数据前备份的原始文件
+fn file_pairs(info: &BookInfo, dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let mut pairs = vec![(resources_dir(info), dir.join(RESOURCES_DIR))];
+
+    let mut files = vec![info.file_path.clone(), backup_path(&info.file_path)];
+    if !info.cover_path.as_os_str().is_empty() {
+        files.extend(cover_files(&info.cover_path));
+    }
+
+    for path in files {
+        if let Some(name) = path.file_name() {
+            pairs.push((path.clone(), dir.join(name)));
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod test {
+    use super::move_files;
+
+    #[test]
+    fn move_test() {
+        let dir = std::env::temp_dir().join("reader_move_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("trash")).unwrap();
+        let (a, b) = (dir.join("a"), dir.join("b"));
+        std::fs::write(&a, "a").unwrap();
+        std::fs::write(&b, "b").unwrap();
+
+        // 第二个文件的目标文件夹不存在, 第一个文件被移回原位置
+        let pairs = vec![
+            (a.clone(), dir.join("trash/a")),
+            (b.clone(), dir.join("missing/b")),
+        ];
+        assert!(move_files(pairs).is_err());
+        assert!(a.exists() && b.exists());
+        assert!(!dir.join("trash/a").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,134 @@
+//! 监视文件夹
+//!
+//! 监视文件夹中新增的书本会自动导入, 原始文件被移除的书本会被标记为 missing
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::warn;
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+
+use crate::{
+    entity::{
+        bookinfo::BookInfo,
+        notification::{Notification, NotificationType},
+    },
+    error::{Error, Result},
+    utils::import_utils::{expand_paths, is_rejected},
+};
+
+/// 文件变化后等待的时间, 避免导入尚未复制完成的文件
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 监视文件夹与书库的对比结果
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    /// 尚未导入的文件, 不包含导入失败且之后没有修改的文件
+    pub new_files: Vec<PathBuf>,
+
+    /// 本次扫描中新发现原始文件被移除的书本
+    pub missing: Vec<BookInfo>,
+}
+
+/// 对比监视文件夹与书库
+///
+/// 找出尚未导入的文件, 并根据原始文件是否存在更新书本的 missing 标记
+pub fn scan_folders(folders: &[PathBuf]) -> Result<ScanReport> {
+    let books = BookInfo::get_info_list()?;
+    let known = books
+        .iter()
+        .map(|book| book.source_path.clone())
+        .collect::<HashSet<_>>();
+
+    let mut report = ScanReport {
+        new_files: expand_paths(folders)
+            .into_iter()
+            .filter(|path| !known.contains(path) && !is_rejected(path))
+            .collect(),
+        ..Default::default()
+    };
+
+    for mut book in books {
+        if !is_watched(&book.source_path, folders) {
+            continue;
+        }
+
+        let missing = !book.source_path.exists();
+        if missing == book.missing {
+            continue;
+        }
+
+        book.missing = missing;
+        BookInfo::update_info(&book)?;
+
+        if missing {
+            report.missing.push(book);
+        }
+    }
+
+    Ok(report)
+}
+
+fn is_watched(path: &Path, folders: &[PathBuf]) -> bool {
+    !path.as_os_str().is_empty() && folders.iter().any(|folder| path.starts_with(folder))
+}
+
+/// 开始监视文件夹, 文件夹中的内容变化时调用 on_change
+///
+/// 返回的 Debouncer 被释放时停止监视
+pub fn watch_folders(
+    folders: &[PathBuf],
+    on_change: impl Fn() + Send + 'static,
+) -> Result<Debouncer<RecommendedWatcher>> {
+    let mut debouncer = new_debouncer(
+        DEBOUNCE_TIMEOUT,
+        move |res: DebounceEventResult| match res {
+            Ok(_) => on_change(),
+            Err(err) => warn!("监视文件夹失败: {}", err),
+        },
+    )
+    .map_err(|err| Error::Io(io::Error::other(err)))?;
+
+    for folder in folders {
+        if let Err(err) = debouncer.watcher().watch(folder, RecursiveMode::Recursive) {
+            warn!("无法监视文件夹 {:?}: {}", folder, err);
+        }
+    }
+
+    Ok(debouncer)
+}
+
+/// 原始文件被移除时展示给用户的提示信息
+pub fn missing_notification(info: &BookInfo) -> Notification {
+    Notification {
+        r#type: NotificationType::Warn,
+        title: "Warn".to_string(),
+        msg: format!(
+            "The file of {} was removed from the watched folder.",
+            info.title
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::is_watched;
+
+    #[test]
+    fn watched_test() {
+        let folders = [PathBuf::from("/books")];
+
+        assert!(is_watched(Path::new("/books/a/b.epub"), &folders));
+        assert!(!is_watched(Path::new("/bookstore/b.epub"), &folders));
+        assert!(!is_watched(Path::new(""), &folders));
+    }
+}
@@ -4,6 +4,7 @@ import { ref } from "vue";
 import pinia from "@/store";
 import { notify } from "@/core/notifyService";
 import { BookMark } from "@/entity/bookMark";
+import Response from "@/entity/response";
 import { useAppStateStore } from "@/store/appStateStore";
 import { randomString } from "@/utils/commonUtils";
 
@@ -102,16 +103,22 @@ async function addBookMark(event: MouseEvent) {
             offset: length,
         },
         create_time: 0,
+        text: target.textContent ?? "",
+        color: "",
+        style: "Highlight",
+        note: "",
+        tags: [],
+        update_time: 0,
     };
 
     // 处理返回值
-    const result: string = await invoke("add_bookmark", {
-        data: JSON.stringify(mark),
-    });
-    const { success, msg } = JSON.parse(result);
+    const { success, notification } = await invoke<Response<null>>(
+        "add_bookmark",
+        { mark: mark },
+    );
 
     if (!success) {
-        notify(msg);
+        notify(notification);
     } else {
         highlight(mark);
     }
@@ -125,19 +132,20 @@ async function refreshBookMark() {
     // });
 
     markId.clear();
-    const result: string = await invoke("get_chapter_mark_list", {
+    const { data, success, notification } = await invoke<
+        Response<BookMark[]>
+    >("get_chapter_mark_list", {
         id: appStateStore.current_book_id,
         chapter: appStateStore.current_chapter,
     });
-    const { success, list, msg } = JSON.parse(result);
 
-    if (success) {
+    if (success && data) {
         // 渲染书签
-        for (const mark of list) {
+        for (const mark of data) {
             highlight(mark);
         }
     } else {
-        notify(msg);
+        notify(notification);
     }
 
     console.log(markId);
@@ -221,8 +229,9 @@ function bookMarkSplit(mark: BookMark): BookMark[] {
         return [];
     }
 
-    let arr = [];
+    let arr: BookMark[] = [];
     arr.push({
+        ...mark,
         book_id: "",
         mark_id: mark.mark_id,
         start_position: {
@@ -246,6 +255,7 @@ function bookMarkSplit(mark: BookMark): BookMark[] {
         index++
     ) {
         arr.push({
+            ...mark,
             book_id: "",
             mark_id: mark.mark_id,
             start_position: {
@@ -264,6 +274,7 @@ function bookMarkSplit(mark: BookMark): BookMark[] {
     }
 
     arr.push({
+        ...mark,
         book_id: "",
         mark_id: mark.mark_id,
         start_position: {
@@ -282,4 +293,22 @@ function bookMarkSplit(mark: BookMark): BookMark[] {
     return arr;
 }
 
-export { setContent, addBookMark, refreshBookMark };
+// 导出当前书本的书签与笔记, format: markdown | json | html
+async function exportBookMark(format: string, path: string) {
+    const { success, notification } = await invoke<Response<string>>(
+        "export_marks",
+        {
+            id: appStateStore.current_book_id,
+            format: format,
+            path: path,
+        },
+    );
+
+    if (!success) {
+        notify(notification);
+    }
+
+    return success;
+}
+
+export { setContent, addBookMark, refreshBookMark, exportBookMark };
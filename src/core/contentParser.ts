@@ -1,5 +1,6 @@
 import { convertFileSrc, invoke } from "@tauri-apps/api/core";
 
+import Response from "@/entity/response";
 import { appendPath } from "@/utils/commonUtils";
 
 // TODO: 完善样式解析
@@ -21,11 +22,11 @@ class Parser {
     private async init() {
         console.log("init");
 
-        const result: string = await invoke("get_css");
-        const { success, css } = JSON.parse(result);
+        const { data: css, success } =
+            await invoke<Response<Record<string, string>>>("get_css");
 
         // 添加样式
-        if (success) {
+        if (success && css) {
             this.css_names = Object.keys(css);
 
             let head = document.head;
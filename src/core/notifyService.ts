@@ -2,7 +2,15 @@ import NotificationItem from "@/components/NotificationItem.vue";
 import Notification from "@/entity/notification";
 import { createApp } from "vue";
 
-export function notify(notification: Notification, duration: number = 5000) {
+export function notify(
+    notification: Notification | null,
+    duration: number = 5000,
+) {
+    // 操作成功且不需要提示时, 后端返回的 notification 为 null
+    if (!notification) {
+        return;
+    }
+
     const panel = document.getElementById("notification-panel")!;
     const item = document.createElement("div");
 
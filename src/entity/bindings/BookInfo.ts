@@ -0,0 +1,75 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type BookInfo = { 
+/**
+ * Epub的唯一标识符
+ */
+id: string, 
+/**
+ * Epub文件存储路径
+ */
+file_path: string, 
+/**
+ * 封面存储路径
+ */
+cover_path: string, 
+/**
+ * 封面缩略图的路径, 按宽度从小到大排列, 根据封面路径得出, 不保存在数据库中
+ */
+thumbnails: Array<string>, 
+/**
+ * 书本标题
+ */
+title: string, 
+/**
+ * 书本作者
+ */
+creator: string, 
+/**
+ * 书本出版日期
+ */
+date: string, 
+/**
+ * 书本出版社
+ */
+publisher: string, 
+/**
+ * 书本语言
+ */
+language: string, 
+/**
+ * 书本分类
+ */
+subject: string, 
+/**
+ * 书本简介
+ */
+description: string, 
+/**
+ * 书本的标识符, 如 ISBN 或 UUID, txt 文件为空
+ */
+identifier: string, 
+/**
+ * 书本所属的系列, 不属于任何系列时为空
+ */
+series: string, 
+/**
+ * 书本在系列中的序号
+ */
+series_index: number, 
+/**
+ * 本书上次打开的时间
+ */
+last_open: number, 
+/**
+ * 本书导入书库的时间
+ */
+add_time: number, 
+/**
+ * 导入时的原始文件路径
+ */
+source_path: string, 
+/**
+ * 原始文件是否已被移除, 仅用于监视文件夹中导入的书本
+ */
+missing: boolean, };
@@ -0,0 +1,29 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { MarkStyle } from "./MarkStyle";
+import type { Position } from "./Position";
+
+export type BookMark = { book_id: string, mark_id: number, start_position: Position, end_position: Position, create_time: number, 
+/**
+ * 书签范围内的文本
+ */
+text: string, 
+/**
+ * 高亮颜色, 使用 CSS 颜色值, 为空时使用默认颜色
+ */
+color: string, 
+/**
+ * 书签样式
+ */
+style: MarkStyle, 
+/**
+ * 笔记内容
+ */
+note: string, 
+/**
+ * 标签
+ */
+tags: Array<string>, 
+/**
+ * 最后一次修改的时间
+ */
+update_time: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 可编辑的书本元数据
+ *
+ * 作者与分类有多个值时以逗号分隔, 与 BookInfo 中的格式一致
+ */
+export type BookMetadata = { title: string, creator: string, date: string, publisher: string, language: string, subject: string, description: string, series: string, series_index: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BookInfo } from "./BookInfo";
+
+/**
+ * 书本列表的一页
+ */
+export type BookPage = { 
+/**
+ * 当前页的书本, 不包含书本简介
+ */
+books: Array<BookInfo>, 
+/**
+ * 满足查询条件的书本总数
+ */
+total: number, };
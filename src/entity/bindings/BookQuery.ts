@@ -0,0 +1,32 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ReadStatus } from "./ReadStatus";
+import type { SortKey } from "./SortKey";
+
+/**
+ * 书本列表的查询条件, 所有条件均为可选, 同时提供时返回同时满足的书本
+ */
+export type BookQuery = { sort: SortKey, 
+/**
+ * 是否倒序排列
+ */
+descending: boolean, 
+/**
+ * 书架的 id
+ */
+shelf?: number, tag?: string, language?: string, 
+/**
+ * 作者, 返回作者中包含该值的书本
+ */
+creator?: string, 
+/**
+ * 分类, 返回分类中包含该值的书本
+ */
+subject?: string, status?: ReadStatus, 
+/**
+ * 关键字, 在标题, 作者, 出版社, 分类与系列中查找
+ */
+keyword?: string, 
+/**
+ * 每页的数量, 为空时返回全部书本
+ */
+limit?: number, offset: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单本书的阅读统计
+ */
+export type BookStats = { book_id: string, title: string, 
+/**
+ * 阅读时长, 单位为毫秒
+ */
+duration: number, words: number, sessions: number, 
+/**
+ * 阅读进度百分比
+ */
+percentage: number, 
+/**
+ * 预计剩余的阅读时长, 单位为毫秒, 无法估计时为 null
+ */
+remaining: number | null, };
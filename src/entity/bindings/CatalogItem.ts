@@ -0,0 +1,46 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 目录项
+ */
+export type CatalogItem = { 
+/**
+ * 目录名
+ */
+label: string, 
+/**
+ * 目录层级, 顶层目录为 1
+ */
+level: number, 
+/**
+ * 目录项的序号, 按阅读顺序从 0 开始
+ */
+index: number, 
+/**
+ * 目录项指向的章节索引(spine 索引), 跳转章节时使用该值
+ */
+target: number, 
+/**
+ * 目录项指向的页面锚点, 对应链接中 # 后的部分
+ */
+anchor: string | null, 
+/**
+ * 目录项的阅读顺序
+ */
+play_order: number, 
+/**
+ * 子目录
+ */
+children: Array<CatalogItem>, 
+/**
+ * 目录项包含的章节的单词数, 包含子目录的章节
+ */
+words: number, 
+/**
+ * 目录项包含的章节的中日文字符数
+ */
+characters: number, 
+/**
+ * 预计的阅读时间, 单位为分钟, 没有字数缓存时为 0
+ */
+minutes: number, };
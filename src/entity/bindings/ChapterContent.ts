@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 跳转章节时返回的内容
+ */
+export type ChapterContent = { 
+/**
+ * 章节的 HTML 内容
+ */
+content: string, 
+/**
+ * 跳转的锚点
+ */
+anchor: string | null, 
+/**
+ * 锚点所在的段落索引, 没有锚点或锚点不存在时为 0
+ */
+paragraph: number, };
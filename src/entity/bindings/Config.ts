@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BookData } from "./BookData";
+import type { Setting } from "./Setting";
+import type { Theme } from "./Theme";
+
+export type Config = { database: string, log: string, book: BookData, theme: Theme, setting: Setting, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ImportStage } from "./ImportStage";
+
+/**
+ * 导入进度, 通过 `import://progress` 事件发送给前端
+ */
+export type ImportProgress = { 
+/**
+ * 正在导入的文件名
+ */
+file: string, stage: ImportStage, 
+/**
+ * 正在导入的文件序号, 从 0 开始
+ */
+index: number, 
+/**
+ * 本次导入的文件总数
+ */
+total: number, 
+/**
+ * 整体进度, 范围为 0 - 100
+ */
+percent: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 单个文件的导入阶段
+ */
+export type ImportStage = "Parsing" | "Saving" | "Indexing" | "Finished";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Notification } from "./Notification";
+
+/**
+ * 导入结束时的汇总, 通过 `import://done` 事件发送给前端
+ */
+export type ImportSummary = { 
+/**
+ * 每个已处理文件的导入结果
+ */
+results: Array<Notification>, 
+/**
+ * 导入是否被取消, 取消前已导入的书本会保留
+ */
+cancelled: boolean, };
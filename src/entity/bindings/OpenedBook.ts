@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Position } from "./Position";
+
+/**
+ * 打开书本时返回的内容
+ */
+export type OpenedBook = { 
+/**
+ * 当前章节的 HTML 内容
+ */
+content: string, 
+/**
+ * 上一次的阅读位置
+ */
+progress: Position, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 统计的周期, 周从星期一开始
+ */
+export type Period = "Day" | "Week";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 阅读状态, 根据阅读进度判断
+ */
+export type ReadStatus = "Unread" | "Reading" | "Finished";
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Position } from "./Position";
+
+export type ReadingProgress = { 
+/**
+ * 书本的唯一标识符
+ */
+book_id: string, 
+/**
+ * 当前阅读的位置
+ */
+position: Position, 
+/**
+ * 阅读进度百分比, 取值范围为 0 ~ 100
+ */
+percentage: number, 
+/**
+ * 进度更新时间
+ */
+update_time: number, };
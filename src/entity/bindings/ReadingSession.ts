@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一次连续的阅读
+ *
+ * 删除书本时保留阅读记录, 使每日统计与连续阅读天数保持不变
+ */
+export type ReadingSession = { 
+/**
+ * 尚未保存的会话为 0
+ */
+id: number, book_id: string, start_time: number, 
+/**
+ * 最后一次阅读活动的时间
+ */
+end_time: number, 
+/**
+ * 本次阅读访问过的章节, 按访问顺序排列
+ */
+chapters: Array<number>, 
+/**
+ * 访问过的章节的总词数, 作为阅读词数的近似值
+ */
+words: number, };
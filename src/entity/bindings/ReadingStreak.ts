@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 连续阅读的天数
+ */
+export type ReadingStreak = { 
+/**
+ * 截至今天的连续天数, 今天尚未阅读时截至昨天
+ */
+current: number, 
+/**
+ * 最长的连续天数
+ */
+longest: number, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 一个周期内的阅读总量
+ */
+export type ReadingTotal = { 
+/**
+ * 周期的起始日期, 格式为 YYYY-MM-DD
+ */
+date: string, 
+/**
+ * 阅读时长, 单位为毫秒
+ */
+duration: number, 
+/**
+ * 阅读的词数, 为近似值
+ */
+words: number, 
+/**
+ * 阅读的次数
+ */
+sessions: number, };
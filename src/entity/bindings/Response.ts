@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Notification } from "./Notification";
+
+/**
+ * 所有 command 统一的返回值
+ *
+ * 成功时 data 为返回的数据, 失败时 notification 为需要展示给用户的提示信息
+ */
+export type Response<T> = { success: boolean, data: T | null, notification: Notification | null, };
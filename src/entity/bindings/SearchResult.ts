@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SearchResult = { 
+/**
+ * 书本的唯一标识符
+ */
+book_id: string, 
+/**
+ * 书本标题
+ */
+title: string, 
+/**
+ * 章节索引(spine 中的索引)
+ */
+chapter: number, 
+/**
+ * 段落索引
+ */
+paragraph: number, 
+/**
+ * 命中内容的片段, 关键字使用 <mark> 标签包裹
+ */
+snippet: string, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BookInfo } from "./BookInfo";
+
+/**
+ * 系列, 包含书库中属于该系列的全部书本
+ */
+export type Series = { 
+/**
+ * 系列名称, 名称只有大小写不同时视为同一系列
+ */
+name: string, 
+/**
+ * 系列中的书本, 按序号排列
+ */
+books: Array<BookInfo>, };
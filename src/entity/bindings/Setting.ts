@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Setting = { sidebar: boolean, 
+/**
+ * 导入 txt 文件时用于识别章节标题的正则表达式
+ */
+chapter_patterns: Array<string>, 
+/**
+ * 监视的文件夹, 文件夹中新增的书本会自动导入
+ */
+watch_folders: Array<string>, 
+/**
+ * 删除的书本在回收站中保留的天数, 为 0 时直接删除
+ */
+trash_days: number, 
+/**
+ * 阅读速度, 每分钟阅读的单词数, 用于估计章节的阅读时间
+ */
+reading_speed: number, 
+/**
+ * 中文与日文的阅读速度, 每分钟阅读的字数
+ */
+cjk_reading_speed: number, };
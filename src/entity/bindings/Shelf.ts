@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 用户创建的书架, 一本书可以放在多个书架中
+ */
+export type Shelf = { id: number, 
+/**
+ * 书架名称, 不可重复
+ */
+name: string, create_time: number, 
+/**
+ * 书架中的书本数量
+ */
+book_count: number, };
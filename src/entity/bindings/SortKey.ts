@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 书本列表的排序方式
+ */
+export type SortKey = "Title" | "Creator" | "LastOpen" | "AddTime" | "Progress";
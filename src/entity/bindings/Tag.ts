@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * 书本的标签, 标签不需要单独创建, 没有书本使用时自动消失
+ */
+export type Tag = { name: string, 
+/**
+ * 使用该标签的书本数量
+ */
+book_count: number, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BookInfo } from "./BookInfo";
+import type { BookMark } from "./BookMark";
+import type { ReadingProgress } from "./ReadingProgress";
+
+/**
+ * 回收站中的书本
+ *
+ * 删除时保存书本的全部数据, 恢复时重新写入数据库
+ */
+export type TrashItem = { info: BookInfo, marks: Array<BookMark>, progress: ReadingProgress | null, 
+/**
+ * 书本所在书架的 id, 恢复时忽略已删除的书架
+ */
+shelves: Array<number>, tags: Array<string>, 
+/**
+ * 删除的时间
+ */
+deleted_time: number, };
@@ -1,13 +1,2 @@
-export default interface BookInfo {
-    id: string;
-    file_path: string;
-    cover_path: string;
-    title: string;
-    creator: string;
-    date: string;
-    pubslisher: string;
-    language: string;
-    subject: string;
-    description: string;
-    last_open: number;
-}
+export type { BookInfo as default } from "./bindings/BookInfo";
+export type { BookMetadata } from "./bindings/BookMetadata";
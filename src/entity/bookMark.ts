@@ -1,15 +1,3 @@
-interface BookMark {
-    book_id: string;
-    mark_id: number;
-    start_position: Position;
-    end_position: Position;
-    create_time: number;
-}
-
-interface Position {
-    chapter: number;
-    paragraph: number;
-    offset: number;
-}
-
-export type { BookMark, Position };
+export type { BookMark } from "./bindings/BookMark";
+export type { Position } from "./bindings/Position";
+export type { MarkStyle } from "./bindings/MarkStyle";
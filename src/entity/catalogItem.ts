@@ -0,0 +1 @@
+export type { CatalogItem as default } from "./bindings/CatalogItem";
@@ -0,0 +1,3 @@
+export type { ImportProgress } from "./bindings/ImportProgress";
+export type { ImportStage } from "./bindings/ImportStage";
+export type { ImportSummary } from "./bindings/ImportSummary";
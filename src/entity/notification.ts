@@ -1,11 +1,2 @@
-export default interface Notification {
-    type: NotificationType;
-    title: string;
-    msg: string;
-}
-
-export enum NotificationType {
-    Err,
-    Warn,
-    Info,
-}
+export type { Notification as default } from "./bindings/Notification";
+export type { NotificationType } from "./bindings/NotificationType";
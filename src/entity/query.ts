@@ -0,0 +1,4 @@
+export type { BookPage } from "./bindings/BookPage";
+export type { BookQuery } from "./bindings/BookQuery";
+export type { ReadStatus } from "./bindings/ReadStatus";
+export type { SortKey } from "./bindings/SortKey";
@@ -0,0 +1,3 @@
+export type { Response as default } from "./bindings/Response";
+export type { OpenedBook } from "./bindings/OpenedBook";
+export type { ChapterContent } from "./bindings/ChapterContent";
@@ -0,0 +1 @@
+export type { Series as default } from "./bindings/Series";
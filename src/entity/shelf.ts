@@ -0,0 +1,2 @@
+export type { Shelf as default } from "./bindings/Shelf";
+export type { Tag } from "./bindings/Tag";
@@ -0,0 +1,4 @@
+export type { BookStats } from "./bindings/BookStats";
+export type { Period } from "./bindings/Period";
+export type { ReadingStreak } from "./bindings/ReadingStreak";
+export type { ReadingTotal } from "./bindings/ReadingTotal";
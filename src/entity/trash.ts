@@ -0,0 +1 @@
+export type { TrashItem as default } from "./bindings/TrashItem";
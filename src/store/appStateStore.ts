@@ -6,9 +6,19 @@ export const useAppStateStore = defineStore(
     () => {
         const current_book_id = ref("");
         const current_chapter = ref(0);
+        // 跳转章节时需要定位的锚点
+        const current_anchor = ref<string | null>(null);
+        // 当前位置所在的目录项序号
+        const current_catalog = ref(-1);
         const sidebar_width = ref(250);
 
-        return { current_book_id, current_chapter, sidebar_width };
+        return {
+            current_book_id,
+            current_chapter,
+            current_anchor,
+            current_catalog,
+            sidebar_width,
+        };
     },
     {
         persist: {
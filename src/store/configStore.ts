@@ -3,22 +3,31 @@ import { reactive, ref } from "vue";
 import { invoke } from "@tauri-apps/api/core";
 
 import pinia from ".";
+import { BookData } from "@/entity/bindings/BookData";
+import { Config } from "@/entity/bindings/Config";
+import { Setting } from "@/entity/bindings/Setting";
+import { Theme } from "@/entity/bindings/Theme";
 
 export const useConfigStore = defineStore("config", {
     state: () => {
         const database = ref("");
         const log = ref("");
-        const book = reactive({
-            info: ref(""),
-            dir: ref(""),
-            cover: ref(""),
-            resources: ref(""),
+        const book = reactive<BookData>({
+            info: "",
+            dir: "",
+            cover: "",
+            resources: "",
         });
-        const theme = reactive({
-            appearance: ref(""),
+        const theme = reactive<Theme>({
+            appearance: "Light",
         });
-        const setting = reactive({
-            sidebar: ref(true),
+        const setting = reactive<Setting>({
+            sidebar: true,
+            chapter_patterns: [],
+            watch_folders: [],
+            trash_days: 30,
+            reading_speed: 250,
+            cjk_reading_speed: 400,
         });
 
         return { database, log, book, theme, setting };
@@ -26,25 +35,14 @@ export const useConfigStore = defineStore("config", {
 
     actions: {
         // 初始化 store
-        initStore(data: {
-            database: string;
-            log: string;
-            book: object;
-            theme: object;
-            setting: object;
-        }) {
+        initStore(data: Config) {
             const { database, log, book, theme, setting } = data;
 
             this.database = database;
             this.log = log;
-            this.book = book as {
-                info: string;
-                dir: string;
-                cover: string;
-                resources: string;
-            };
-            this.theme = theme as { appearance: string };
-            this.setting = setting as { sidebar: boolean };
+            this.book = book;
+            this.theme = theme;
+            this.setting = setting;
         },
     },
 });
@@ -53,13 +51,13 @@ export const useConfigStore = defineStore("config", {
 pinia.use(({ store }) => {
     store.$subscribe(() => {
         if (store.$id === "config") {
-            const newData = JSON.stringify({
+            const newData: Config = {
                 database: store.database,
                 log: store.log,
                 book: store.book,
                 theme: store.theme,
                 setting: store.setting,
-            });
+            };
             invoke("update_config", { config: newData });
         }
     });